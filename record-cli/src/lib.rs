@@ -0,0 +1,611 @@
+//! Command-line tool for managing record accounts.
+//!
+//! Commands talk to the cluster through [`RecordRpc`], implemented for
+//! [`RpcClient`], so they can run against any bank in tests.
+
+use {
+    base64::{engine::general_purpose::STANDARD, Engine},
+    clap::{Args, Parser, Subcommand, ValueEnum},
+    record::{
+        client::{self, RecordAccount, RecordConfig, SourceValue},
+        instruction::Comparison,
+        state::RecordData,
+        value::{Encoding, ValueType},
+    },
+    solana_account_decoder_client_types::UiAccountEncoding,
+    solana_rpc_client::rpc_client::RpcClient,
+    solana_rpc_client_api::{
+        config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+        filter::{Memcmp, RpcFilterType},
+    },
+    solana_sdk::{
+        account::Account,
+        hash::Hash,
+        instruction::Instruction,
+        message::Message,
+        pubkey::Pubkey,
+        signature::{read_keypair_file, Keypair, Signature, Signer},
+        transaction::Transaction,
+    },
+    std::fmt::Write,
+};
+
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// Offset of the authority in the record header, after the version byte
+const AUTHORITY_OFFSET: usize = 1;
+
+#[derive(Debug, Parser)]
+#[command(name = "record-cli", version, about = "Manage record accounts")]
+pub struct Cli {
+    /// RPC endpoint of the cluster
+    #[arg(
+        long,
+        short = 'u',
+        global = true,
+        env = "RECORD_RPC_URL",
+        default_value = "http://localhost:8899"
+    )]
+    pub url: String,
+
+    /// Keypair of the record authority, which also pays the fees
+    #[arg(
+        long,
+        short = 'k',
+        global = true,
+        default_value = "~/.config/solana/id.json"
+    )]
+    pub keypair: String,
+
+    /// Print the unsigned transaction in this encoding instead of sending it
+    #[arg(long, global = true, value_enum)]
+    pub unsigned: Option<TransactionEncoding>,
+
+    /// Authority and fee payer of an unsigned transaction, instead of the
+    /// keypair
+    #[arg(long, global = true, requires = "unsigned")]
+    pub authority: Option<Pubkey>,
+
+    /// Blockhash of an unsigned transaction, fetched from the cluster if
+    /// omitted
+    #[arg(long, global = true, requires = "unsigned")]
+    pub blockhash: Option<Hash>,
+
+    /// Address of the record program
+    #[arg(long, global = true, env = "RECORD_PROGRAM_ID", default_value_t = client::id())]
+    pub program_id: Pubkey,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum TransactionEncoding {
+    Base58,
+    Base64,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Create and initialize a record account
+    Create {
+        /// Number of slots
+        #[arg(long, default_value_t = 1)]
+        slots: u8,
+
+        /// Create the record at the address derived from the authority and
+        /// this seed
+        #[arg(long)]
+        seed: Option<String>,
+
+        #[command(flatten)]
+        config: ConfigArgs,
+    },
+    /// Record the current value of a source account in a slot
+    Write {
+        #[command(flatten)]
+        target: Target,
+
+        /// Allow checks of the slot to read from any source account
+        #[arg(long)]
+        unpinned: bool,
+    },
+    /// Check the current value of a source account against a slot
+    #[command(subcommand)]
+    Check(Check),
+    /// Print a record account
+    Show { record: Pubkey },
+    /// Hand a record over to a new authority
+    SetAuthority {
+        record: Pubkey,
+        new_authority: Pubkey,
+    },
+    /// Close a record and reclaim its lamports
+    Close {
+        record: Pubkey,
+
+        /// Receiver of the lamports, the authority if omitted
+        #[arg(long)]
+        destination: Option<Pubkey>,
+    },
+    /// List the records of an authority, the keypair if omitted
+    ListByAuthority { authority: Option<Pubkey> },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Check {
+    /// The value increased by at least `amount`
+    Add {
+        #[command(flatten)]
+        target: Target,
+        amount: u64,
+    },
+    /// The value increased by at least `bps` basis points
+    AddBps {
+        #[command(flatten)]
+        target: Target,
+        bps: u16,
+    },
+    /// The value decreased by an amount within `[min, max]`
+    Sub {
+        #[command(flatten)]
+        target: Target,
+        min: u64,
+        max: u64,
+    },
+    /// The value compares to the recorded value plus `operand`
+    Compare {
+        #[command(flatten)]
+        target: Target,
+        #[arg(value_enum)]
+        op: CompareOp,
+        operand: u64,
+    },
+}
+
+/// Optional restrictions of a new record
+#[derive(Debug, Args)]
+pub struct ConfigArgs {
+    /// Program that must own every source account
+    #[arg(long)]
+    pub source_owner: Option<Pubkey>,
+
+    /// Mint required for token accounts
+    #[arg(long)]
+    pub token_mint: Option<Pubkey>,
+
+    /// Owner required for token accounts
+    #[arg(long)]
+    pub token_owner: Option<Pubkey>,
+}
+
+/// Record slot and the source account value it tracks
+#[derive(Debug, Args)]
+pub struct Target {
+    /// Record account
+    pub record: Pubkey,
+
+    /// Account the value is read from
+    pub source: Pubkey,
+
+    /// Index of the record slot
+    #[arg(long, default_value_t = 0)]
+    pub slot: u8,
+
+    /// What to read from the source account
+    #[arg(long, value_enum, default_value_t = SourceKind::Value)]
+    pub from: SourceKind,
+
+    /// Offset of the value in the source account data
+    #[arg(long, default_value_t = 0)]
+    pub offset: u64,
+
+    /// Type of the value
+    #[arg(long = "type", value_parser = parse_value_type, default_value = "u64")]
+    pub value_type: ValueType,
+
+    /// The value is stored big-endian
+    #[arg(long)]
+    pub big_endian: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum SourceKind {
+    /// Integer at `--offset` of the account data
+    Value,
+    /// Amount of an SPL Token or Token-2022 account
+    Token,
+    /// Supply of a mint
+    Mint,
+    /// Lamport balance
+    Lamports,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl From<CompareOp> for Comparison {
+    fn from(op: CompareOp) -> Self {
+        match op {
+            CompareOp::Eq => Self::Eq,
+            CompareOp::Ne => Self::Ne,
+            CompareOp::Lt => Self::Lt,
+            CompareOp::Le => Self::Le,
+            CompareOp::Gt => Self::Gt,
+            CompareOp::Ge => Self::Ge,
+        }
+    }
+}
+
+const VALUE_TYPES: [ValueType; 10] = [
+    ValueType::U8,
+    ValueType::U16,
+    ValueType::U32,
+    ValueType::U64,
+    ValueType::U128,
+    ValueType::I8,
+    ValueType::I16,
+    ValueType::I32,
+    ValueType::I64,
+    ValueType::I128,
+];
+
+fn parse_value_type(name: &str) -> std::result::Result<ValueType, String> {
+    VALUE_TYPES
+        .into_iter()
+        .find(|value_type| value_type.to_string() == name)
+        .ok_or_else(|| format!("unknown value type `{name}`, expected one of u8..u128, i8..i128"))
+}
+
+impl Target {
+    fn value(&self) -> SourceValue {
+        SourceValue {
+            offset: self.offset,
+            value_type: self.value_type,
+            encoding: Encoding {
+                big_endian: self.big_endian,
+                bit_field: None,
+            },
+        }
+    }
+
+    /// Fails for checks that only read integers from the account data
+    fn require_value(&self, check: &str) -> Result<SourceValue> {
+        if self.from != SourceKind::Value {
+            return Err(format!("`check {check}` only reads values, drop `--from`").into());
+        }
+        Ok(self.value())
+    }
+}
+
+/// Cluster access needed by the commands
+pub trait RecordRpc {
+    fn account(&self, pubkey: &Pubkey) -> Result<Option<Account>>;
+
+    fn minimum_balance(&self, data_len: usize) -> Result<u64>;
+
+    fn latest_blockhash(&self) -> Result<Hash>;
+
+    /// Sends the transaction and waits for its confirmation
+    fn send(&self, transaction: &Transaction) -> Result<Signature>;
+
+    /// Record accounts of `authority`, owned by the record program at
+    /// `program_id`
+    fn record_accounts(
+        &self,
+        program_id: &Pubkey,
+        authority: &Pubkey,
+    ) -> Result<Vec<(Pubkey, Account)>>;
+}
+
+impl RecordRpc for RpcClient {
+    fn account(&self, pubkey: &Pubkey) -> Result<Option<Account>> {
+        Ok(self
+            .get_account_with_commitment(pubkey, self.commitment())?
+            .value)
+    }
+
+    fn minimum_balance(&self, data_len: usize) -> Result<u64> {
+        Ok(self.get_minimum_balance_for_rent_exemption(data_len)?)
+    }
+
+    fn latest_blockhash(&self) -> Result<Hash> {
+        Ok(self.get_latest_blockhash()?)
+    }
+
+    fn send(&self, transaction: &Transaction) -> Result<Signature> {
+        Ok(self.send_and_confirm_transaction(transaction)?)
+    }
+
+    fn record_accounts(
+        &self,
+        program_id: &Pubkey,
+        authority: &Pubkey,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(vec![
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(0, vec![RecordData::CURRENT_VERSION])),
+                RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                    AUTHORITY_OFFSET,
+                    authority.to_bytes().to_vec(),
+                )),
+            ]),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..RpcAccountInfoConfig::default()
+            },
+            ..RpcProgramAccountsConfig::default()
+        };
+        Ok(self.get_program_accounts_with_config(program_id, config)?)
+    }
+}
+
+/// Loads the keypair, unless an unsigned transaction is printed for
+/// `--authority`
+pub fn load_keypair(cli: &Cli) -> Result<Option<Keypair>> {
+    if cli.unsigned.is_some() && cli.authority.is_some() {
+        return Ok(None);
+    }
+    let path = match (cli.keypair.strip_prefix("~/"), std::env::var("HOME")) {
+        (Some(path), Ok(home)) => format!("{home}/{path}"),
+        _ => cli.keypair.clone(),
+    };
+    read_keypair_file(&path)
+        .map(Some)
+        .map_err(|err| format!("failed to read keypair `{path}`: {err}").into())
+}
+
+/// Runs the command and returns its output
+pub fn run(cli: &Cli, rpc: &impl RecordRpc, keypair: Option<&Keypair>) -> Result<String> {
+    let authority = match (cli.authority, keypair) {
+        (Some(authority), _) => authority,
+        (None, Some(keypair)) => keypair.pubkey(),
+        (None, None) => return Err("a keypair or `--authority` is required".into()),
+    };
+    let program_id = &cli.program_id;
+    let sender = Sender {
+        cli,
+        rpc,
+        keypair,
+        authority,
+    };
+
+    match &cli.command {
+        Command::Create {
+            slots,
+            seed,
+            config,
+        } => {
+            let config = RecordConfig {
+                source_owner: config.source_owner,
+                token_mint: config.token_mint,
+                token_owner: config.token_owner,
+            };
+            match seed {
+                Some(seed) => {
+                    let instruction = client::initialize_pda(
+                        program_id,
+                        &authority,
+                        &authority,
+                        seed.as_bytes(),
+                        *slots,
+                        &config,
+                    )
+                    .map_err(|err| format!("invalid seed `{seed}`: {err:?}"))?;
+                    let (record, _) =
+                        client::find_record_address(program_id, &authority, seed.as_bytes());
+                    let output = sender.submit(&[instruction], &[])?;
+                    Ok(format!("Record: {record}\n{output}"))
+                }
+                None => {
+                    if cli.unsigned.is_some() {
+                        return Err(
+                            "unsigned records must be created with `--seed`, the record keypair has to sign otherwise"
+                                .into(),
+                        );
+                    }
+                    let record = Keypair::new();
+                    let lamports = rpc.minimum_balance(client::account_len(*slots))?;
+                    let instructions = [
+                        client::create_account(
+                            program_id,
+                            &authority,
+                            &record.pubkey(),
+                            *slots,
+                            lamports,
+                        ),
+                        client::initialize_with_config(
+                            program_id,
+                            &record.pubkey(),
+                            &authority,
+                            &config,
+                        ),
+                    ];
+                    let output = sender.submit(&instructions, &[&record])?;
+                    Ok(format!("Record: {}\n{output}", record.pubkey()))
+                }
+            }
+        }
+        Command::Write { target, unpinned } => {
+            let (record, source, slot) = (&target.record, &target.source, target.slot);
+            let instruction = match target.from {
+                SourceKind::Value => client::write_u64(
+                    program_id,
+                    record,
+                    &authority,
+                    source,
+                    slot,
+                    target.value(),
+                    !unpinned,
+                ),
+                SourceKind::Token => {
+                    client::write_token_amount(program_id, record, &authority, source, slot)
+                }
+                SourceKind::Mint => {
+                    client::write_mint_supply(program_id, record, &authority, source, slot)
+                }
+                SourceKind::Lamports => {
+                    client::write_lamports(program_id, record, &authority, source, slot)
+                }
+            };
+            sender.submit(&[instruction], &[])
+        }
+        Command::Check(check) => {
+            let instruction = match check {
+                Check::Add { target, amount } => {
+                    let (record, source, slot) = (&target.record, &target.source, target.slot);
+                    match target.from {
+                        SourceKind::Value => client::check_add(
+                            program_id,
+                            record,
+                            &authority,
+                            source,
+                            slot,
+                            target.value(),
+                            *amount,
+                        ),
+                        SourceKind::Token => client::check_token_add(
+                            program_id, record, &authority, source, slot, *amount,
+                        ),
+                        SourceKind::Mint => client::check_mint_supply_add(
+                            program_id, record, &authority, source, slot, *amount,
+                        ),
+                        SourceKind::Lamports => client::check_lamports_add(
+                            program_id, record, &authority, source, slot, *amount,
+                        ),
+                    }
+                }
+                Check::AddBps { target, bps } => client::check_add_bps(
+                    program_id,
+                    &target.record,
+                    &authority,
+                    &target.source,
+                    target.slot,
+                    target.require_value("add-bps")?,
+                    *bps,
+                ),
+                Check::Sub { target, min, max } => client::check_sub(
+                    program_id,
+                    &target.record,
+                    &authority,
+                    &target.source,
+                    target.slot,
+                    target.require_value("sub")?,
+                    *min,
+                    *max,
+                ),
+                Check::Compare {
+                    target,
+                    op,
+                    operand,
+                } => client::check_compare(
+                    program_id,
+                    &target.record,
+                    &authority,
+                    &target.source,
+                    target.slot,
+                    target.require_value("compare")?,
+                    (*op).into(),
+                    *operand,
+                ),
+            };
+            sender.submit(&[instruction], &[])
+        }
+        Command::Show { record } => {
+            let account = rpc
+                .account(record)?
+                .ok_or_else(|| format!("record {record} not found"))?;
+            if &account.owner != program_id {
+                return Err(format!("{record} is not owned by the record program").into());
+            }
+            let decoded = RecordAccount::unpack(&account.data)
+                .map_err(|err| format!("{record} is not a valid record: {err:?}"))?;
+            Ok(format!(
+                "Record: {record}\nLamports: {}\n{decoded}",
+                account.lamports
+            ))
+        }
+        Command::SetAuthority {
+            record,
+            new_authority,
+        } => sender.submit(
+            &[client::set_authority(
+                program_id,
+                record,
+                &authority,
+                new_authority,
+            )],
+            &[],
+        ),
+        Command::Close {
+            record,
+            destination,
+        } => {
+            let destination = destination.unwrap_or(authority);
+            sender.submit(
+                &[client::close_account(
+                    program_id,
+                    record,
+                    &authority,
+                    &destination,
+                )],
+                &[],
+            )
+        }
+        Command::ListByAuthority { authority: owner } => {
+            let owner = owner.unwrap_or(authority);
+            let mut output = String::new();
+            for (pubkey, account) in rpc.record_accounts(program_id, &owner)? {
+                // other versions and closed records share the program
+                let Ok(record) = RecordAccount::unpack(&account.data) else {
+                    continue;
+                };
+                if record.authority == owner {
+                    writeln!(output, "{pubkey} {} slots", record.slots.len())?;
+                }
+            }
+            Ok(output.trim_end().to_string())
+        }
+    }
+}
+
+/// Signs and sends transactions, or prints them unsigned
+struct Sender<'a, R> {
+    cli: &'a Cli,
+    rpc: &'a R,
+    keypair: Option<&'a Keypair>,
+    authority: Pubkey,
+}
+
+impl<R: RecordRpc> Sender<'_, R> {
+    fn submit(&self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<String> {
+        let blockhash = match self.cli.blockhash {
+            Some(blockhash) => blockhash,
+            None => self.rpc.latest_blockhash()?,
+        };
+        let message = Message::new_with_blockhash(instructions, Some(&self.authority), &blockhash);
+
+        if let Some(encoding) = self.cli.unsigned {
+            let transaction = bincode::serialize(&Transaction::new_unsigned(message))?;
+            return Ok(match encoding {
+                TransactionEncoding::Base58 => bs58::encode(transaction).into_string(),
+                TransactionEncoding::Base64 => STANDARD.encode(transaction),
+            });
+        }
+
+        let keypair = self.keypair.ok_or("a keypair is required to sign")?;
+        let mut transaction = Transaction::new_unsigned(message);
+        let mut all_signers = vec![keypair];
+        all_signers.extend_from_slice(signers);
+        transaction.try_sign(&all_signers, blockhash)?;
+        let signature = self.rpc.send(&transaction)?;
+        Ok(format!("Signature: {signature}"))
+    }
+}
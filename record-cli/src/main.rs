@@ -0,0 +1,20 @@
+use {
+    clap::Parser,
+    record_cli::{load_keypair, run, Cli},
+    solana_rpc_client::rpc_client::RpcClient,
+    solana_sdk::commitment_config::CommitmentConfig,
+};
+
+fn main() {
+    let cli = Cli::parse();
+    let rpc = RpcClient::new_with_commitment(cli.url.clone(), CommitmentConfig::confirmed());
+
+    let result = load_keypair(&cli).and_then(|keypair| run(&cli, &rpc, keypair.as_ref()));
+    match result {
+        Ok(output) => println!("{output}"),
+        Err(err) => {
+            eprintln!("error: {err}");
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,551 @@
+use {
+    base64::{engine::general_purpose::STANDARD, Engine},
+    clap::Parser,
+    record::{
+        client::{self, SourceValue},
+        state::RecordData,
+        value::{Encoding, ValueType},
+    },
+    record_cli::{run, Cli, RecordRpc, Result},
+    solana_program_test::ProgramTest,
+    solana_sdk::{
+        account::Account,
+        hash::Hash,
+        instruction::Instruction,
+        message::Message,
+        pubkey::Pubkey,
+        signature::{Keypair, Signature, Signer},
+        transaction::Transaction,
+    },
+    std::{cell::RefCell, collections::HashMap},
+};
+
+/// In-memory cluster that keeps every sent transaction
+struct MockRpc {
+    blockhash: Hash,
+    accounts: HashMap<Pubkey, Account>,
+    sent: RefCell<Vec<Transaction>>,
+}
+
+impl MockRpc {
+    fn new() -> Self {
+        Self {
+            blockhash: Hash::new_unique(),
+            accounts: HashMap::new(),
+            sent: RefCell::default(),
+        }
+    }
+
+    fn add_record(&mut self, authority: &Pubkey, slot_count: u8) -> Pubkey {
+        let mut data = vec![0; RecordData::account_len(slot_count)];
+        let header =
+            bytemuck::from_bytes_mut::<RecordData>(&mut data[..RecordData::WRITABLE_START_INDEX]);
+        header.version = RecordData::CURRENT_VERSION;
+        header.authority = authority.to_bytes();
+        header.slot_count = slot_count;
+        self.add_account(client::id(), data)
+    }
+
+    fn add_account(&mut self, owner: Pubkey, data: Vec<u8>) -> Pubkey {
+        let pubkey = Pubkey::new_unique();
+        self.accounts.insert(
+            pubkey,
+            Account {
+                lamports: 1_000_000,
+                data,
+                owner,
+                executable: false,
+                rent_epoch: 0,
+            },
+        );
+        pubkey
+    }
+
+    fn single_sent(&self) -> Transaction {
+        let sent = self.sent.borrow();
+        assert_eq!(sent.len(), 1);
+        sent[0].clone()
+    }
+}
+
+impl RecordRpc for MockRpc {
+    fn account(&self, pubkey: &Pubkey) -> Result<Option<Account>> {
+        Ok(self.accounts.get(pubkey).cloned())
+    }
+
+    fn minimum_balance(&self, data_len: usize) -> Result<u64> {
+        Ok(data_len as u64 * 10)
+    }
+
+    fn latest_blockhash(&self) -> Result<Hash> {
+        Ok(self.blockhash)
+    }
+
+    fn send(&self, transaction: &Transaction) -> Result<Signature> {
+        transaction.verify()?;
+        self.sent.borrow_mut().push(transaction.clone());
+        Ok(transaction.signatures[0])
+    }
+
+    fn record_accounts(
+        &self,
+        program_id: &Pubkey,
+        authority: &Pubkey,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        Ok(self
+            .accounts
+            .iter()
+            .filter(|(_, account)| {
+                &account.owner == program_id && account.data.get(1..33) == Some(authority.as_ref())
+            })
+            .map(|(pubkey, account)| (*pubkey, account.clone()))
+            .collect())
+    }
+}
+
+fn parse(args: &[&str]) -> Cli {
+    Cli::try_parse_from(["record-cli"].iter().chain(args)).unwrap()
+}
+
+fn message(instructions: &[Instruction], payer: &Pubkey, blockhash: &Hash) -> Message {
+    Message::new_with_blockhash(instructions, Some(payer), blockhash)
+}
+
+#[test]
+fn write_signs_and_sends() {
+    let rpc = MockRpc::new();
+    let keypair = Keypair::new();
+    let record = Pubkey::new_unique();
+    let source = Pubkey::new_unique();
+
+    let cli = parse(&[
+        "write",
+        &record.to_string(),
+        &source.to_string(),
+        "--slot",
+        "2",
+        "--offset",
+        "8",
+        "--type",
+        "i32",
+        "--big-endian",
+        "--unpinned",
+    ]);
+    let output = run(&cli, &rpc, Some(&keypair)).unwrap();
+
+    let transaction = rpc.single_sent();
+    assert_eq!(output, format!("Signature: {}", transaction.signatures[0]));
+    let value = SourceValue {
+        offset: 8,
+        value_type: ValueType::I32,
+        encoding: Encoding {
+            big_endian: true,
+            bit_field: None,
+        },
+    };
+    assert_eq!(
+        transaction.message,
+        message(
+            &[client::write_u64(
+                &client::id(),
+                &record,
+                &keypair.pubkey(),
+                &source,
+                2,
+                value,
+                false
+            )],
+            &keypair.pubkey(),
+            &rpc.blockhash
+        )
+    );
+}
+
+#[test]
+fn check_sources() {
+    let keypair = Keypair::new();
+    let record = Pubkey::new_unique();
+    let source = Pubkey::new_unique();
+    let authority = keypair.pubkey();
+    let (record_arg, source_arg) = (record.to_string(), source.to_string());
+
+    let cases = [
+        (
+            vec![
+                "check",
+                "add",
+                &record_arg,
+                &source_arg,
+                "5",
+                "--from",
+                "token",
+            ],
+            client::check_token_add(&client::id(), &record, &authority, &source, 0, 5),
+        ),
+        (
+            vec![
+                "check",
+                "add",
+                &record_arg,
+                &source_arg,
+                "5",
+                "--from",
+                "lamports",
+            ],
+            client::check_lamports_add(&client::id(), &record, &authority, &source, 0, 5),
+        ),
+        (
+            vec![
+                "check",
+                "add-bps",
+                &record_arg,
+                &source_arg,
+                "250",
+                "--offset",
+                "64",
+            ],
+            client::check_add_bps(
+                &client::id(),
+                &record,
+                &authority,
+                &source,
+                0,
+                SourceValue::u64(64),
+                250,
+            ),
+        ),
+        (
+            vec![
+                "check",
+                "sub",
+                &record_arg,
+                &source_arg,
+                "1",
+                "3",
+                "--slot",
+                "1",
+            ],
+            client::check_sub(
+                &client::id(),
+                &record,
+                &authority,
+                &source,
+                1,
+                SourceValue::u64(0),
+                1,
+                3,
+            ),
+        ),
+    ];
+    for (args, expected) in cases {
+        let rpc = MockRpc::new();
+        run(&parse(&args), &rpc, Some(&keypair)).unwrap();
+        assert_eq!(
+            rpc.single_sent().message,
+            message(&[expected], &authority, &rpc.blockhash)
+        );
+    }
+
+    let rpc = MockRpc::new();
+    let cli = parse(&[
+        "check",
+        "sub",
+        &record_arg,
+        &source_arg,
+        "1",
+        "3",
+        "--from",
+        "mint",
+    ]);
+    assert!(run(&cli, &rpc, Some(&keypair)).is_err());
+    assert!(rpc.sent.borrow().is_empty());
+}
+
+#[test]
+fn create_record() {
+    let rpc = MockRpc::new();
+    let keypair = Keypair::new();
+
+    let output = run(&parse(&["create", "--slots", "3"]), &rpc, Some(&keypair)).unwrap();
+    let transaction = rpc.single_sent();
+    let record = transaction.message.account_keys[1];
+    assert!(output.starts_with(&format!("Record: {record}\n")));
+    assert_eq!(
+        transaction.message,
+        message(
+            &[
+                client::create_account(
+                    &client::id(),
+                    &keypair.pubkey(),
+                    &record,
+                    3,
+                    client::account_len(3) as u64 * 10
+                ),
+                client::initialize(&client::id(), &record, &keypair.pubkey()),
+            ],
+            &keypair.pubkey(),
+            &rpc.blockhash
+        )
+    );
+
+    let rpc = MockRpc::new();
+    let output = run(
+        &parse(&["create", "--slots", "2", "--seed", "vault"]),
+        &rpc,
+        Some(&keypair),
+    )
+    .unwrap();
+    let (record, _) = client::find_record_address(&client::id(), &keypair.pubkey(), b"vault");
+    assert!(output.starts_with(&format!("Record: {record}\n")));
+}
+
+#[test]
+fn print_unsigned_transaction() {
+    let rpc = MockRpc::new();
+    let authority = Pubkey::new_unique();
+    let record = Pubkey::new_unique();
+    let new_authority = Pubkey::new_unique();
+    let blockhash = Hash::new_unique();
+
+    let args = [
+        "set-authority".to_string(),
+        record.to_string(),
+        new_authority.to_string(),
+        "--authority".to_string(),
+        authority.to_string(),
+        "--blockhash".to_string(),
+        blockhash.to_string(),
+    ];
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+    let expected = message(
+        &[client::set_authority(
+            &client::id(),
+            &record,
+            &authority,
+            &new_authority,
+        )],
+        &authority,
+        &blockhash,
+    );
+
+    let base64 = run(
+        &parse(&[&args[..], &["--unsigned", "base64"]].concat()),
+        &rpc,
+        None,
+    )
+    .unwrap();
+    let transaction: Transaction = bincode::deserialize(&STANDARD.decode(base64).unwrap()).unwrap();
+    assert_eq!(transaction.message, expected);
+    assert_eq!(transaction.signatures, vec![Signature::default()]);
+
+    let base58 = run(
+        &parse(&[&args[..], &["--unsigned", "base58"]].concat()),
+        &rpc,
+        None,
+    )
+    .unwrap();
+    let transaction: Transaction =
+        bincode::deserialize(&bs58::decode(base58).into_vec().unwrap()).unwrap();
+    assert_eq!(transaction.message, expected);
+    assert!(rpc.sent.borrow().is_empty());
+
+    // the record keypair would have to sign as well
+    let cli = parse(&[
+        "create",
+        "--unsigned",
+        "base64",
+        "--authority",
+        &authority.to_string(),
+    ]);
+    assert!(run(&cli, &rpc, None).is_err());
+
+    // --authority only applies to unsigned transactions
+    assert!(Cli::try_parse_from([
+        "record-cli",
+        "close",
+        &record.to_string(),
+        "--authority",
+        &authority.to_string(),
+    ])
+    .is_err());
+}
+
+#[test]
+fn show_and_list_records() {
+    let mut rpc = MockRpc::new();
+    let authority = Keypair::new();
+    let record = rpc.add_record(&authority.pubkey(), 2);
+    let other_authority = Pubkey::new_unique();
+    let other = rpc.add_record(&other_authority, 1);
+    let mut closed = vec![RecordData::CLOSED_VERSION];
+    closed.extend_from_slice(authority.pubkey().as_ref());
+    rpc.add_account(client::id(), closed);
+    let foreign = rpc.add_account(Pubkey::new_unique(), vec![0; 200]);
+
+    let output = run(
+        &parse(&["show", &record.to_string()]),
+        &rpc,
+        Some(&authority),
+    )
+    .unwrap();
+    assert!(output.starts_with(&format!(
+        "Record: {record}\nLamports: 1000000\nVersion: 2\n"
+    )));
+    assert!(output.contains(&format!("Authority: {}", authority.pubkey())));
+    assert!(output.contains("Slots: 2"));
+
+    assert!(run(
+        &parse(&["show", &foreign.to_string()]),
+        &rpc,
+        Some(&authority)
+    )
+    .is_err());
+    let missing = Pubkey::new_unique().to_string();
+    assert!(run(&parse(&["show", &missing]), &rpc, Some(&authority)).is_err());
+
+    let output = run(&parse(&["list-by-authority"]), &rpc, Some(&authority)).unwrap();
+    assert_eq!(output, format!("{record} 2 slots"));
+
+    let output = run(
+        &parse(&["list-by-authority", &other_authority.to_string()]),
+        &rpc,
+        Some(&authority),
+    )
+    .unwrap();
+    assert_eq!(output, format!("{other} 1 slots"));
+}
+
+#[test]
+fn custom_program_id() {
+    let rpc = MockRpc::new();
+    let keypair = Keypair::new();
+    let program_id = Pubkey::new_unique();
+    let record = Pubkey::new_unique();
+
+    let cli = parse(&[
+        "--program-id",
+        &program_id.to_string(),
+        "close",
+        &record.to_string(),
+    ]);
+    run(&cli, &rpc, Some(&keypair)).unwrap();
+    assert_eq!(
+        rpc.single_sent().message,
+        message(
+            &[client::close_account(
+                &program_id,
+                &record,
+                &keypair.pubkey(),
+                &keypair.pubkey()
+            )],
+            &keypair.pubkey(),
+            &rpc.blockhash
+        )
+    );
+}
+
+#[test]
+fn parse_value_type_fail() {
+    let record = Pubkey::new_unique().to_string();
+    assert!(
+        Cli::try_parse_from(["record-cli", "write", &record, &record, "--type", "u256"]).is_err()
+    );
+}
+
+/// Cluster backed by the `solana-program-test` bank, running the deployed
+/// record program
+struct BankRpc {
+    runtime: tokio::runtime::Runtime,
+    banks_client: solana_program_test::BanksClient,
+}
+
+impl RecordRpc for BankRpc {
+    fn account(&self, pubkey: &Pubkey) -> Result<Option<Account>> {
+        Ok(self
+            .runtime
+            .block_on(self.banks_client.get_account(*pubkey))?)
+    }
+
+    fn minimum_balance(&self, data_len: usize) -> Result<u64> {
+        let rent = self.runtime.block_on(self.banks_client.get_rent())?;
+        Ok(rent.minimum_balance(data_len))
+    }
+
+    fn latest_blockhash(&self) -> Result<Hash> {
+        Ok(self
+            .runtime
+            .block_on(self.banks_client.get_latest_blockhash())?)
+    }
+
+    fn send(&self, transaction: &Transaction) -> Result<Signature> {
+        self.runtime
+            .block_on(self.banks_client.process_transaction(transaction.clone()))?;
+        Ok(transaction.signatures[0])
+    }
+
+    fn record_accounts(
+        &self,
+        _program_id: &Pubkey,
+        _authority: &Pubkey,
+    ) -> Result<Vec<(Pubkey, Account)>> {
+        Err("the bank does not index program accounts".into())
+    }
+}
+
+#[test]
+fn bank_record_lifecycle() {
+    let mut program_test = ProgramTest::new("record", client::id(), None);
+    let source = Pubkey::new_unique();
+    program_test.add_account(
+        source,
+        Account {
+            lamports: 1_000_000,
+            data: 999u64.to_le_bytes().to_vec(),
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let context = runtime.block_on(program_test.start_with_context());
+    let keypair = context.payer.insecure_clone();
+    let rpc = BankRpc {
+        runtime,
+        banks_client: context.banks_client.clone(),
+    };
+
+    let output = run(
+        &parse(&["create", "--slots", "1", "--seed", "bank"]),
+        &rpc,
+        Some(&keypair),
+    )
+    .unwrap();
+    let (record, _) = client::find_record_address(&client::id(), &keypair.pubkey(), b"bank");
+    assert!(output.starts_with(&format!("Record: {record}")));
+
+    let (record_arg, source_arg) = (record.to_string(), source.to_string());
+    run(
+        &parse(&["write", &record_arg, &source_arg]),
+        &rpc,
+        Some(&keypair),
+    )
+    .unwrap();
+    let output = run(&parse(&["show", &record_arg]), &rpc, Some(&keypair)).unwrap();
+    assert!(output.contains(&format!("[0] u64 999 from {source} at offset 0, pinned")));
+
+    run(
+        &parse(&["check", "add", &record_arg, &source_arg, "0"]),
+        &rpc,
+        Some(&keypair),
+    )
+    .unwrap();
+    assert!(run(
+        &parse(&["check", "add", &record_arg, &source_arg, "1"]),
+        &rpc,
+        Some(&keypair),
+    )
+    .is_err());
+
+    run(&parse(&["close", &record_arg]), &rpc, Some(&keypair)).unwrap();
+    assert!(run(&parse(&["show", &record_arg]), &rpc, Some(&keypair)).is_err());
+}
@@ -0,0 +1,44 @@
+//! Writes the program id to `$OUT_DIR/program_id.rs`, from the base58
+//! `RECORD_PROGRAM_ID` environment variable, or the localnet id with the
+//! `localnet` feature.
+
+use std::{env, fs, path::Path};
+
+const PROGRAM_ID_VAR: &str = "RECORD_PROGRAM_ID";
+
+/// Program id of builds without `RECORD_PROGRAM_ID`. Unlike an all-zero id it
+/// is not the System Program, whose accounts would pass the record owner check.
+const PLACEHOLDER_ID: &str = "RecordP1aceho1der11111111111111111111111111";
+
+/// Program id of the tests and local validators, used by builds with the
+/// `localnet` feature and without `RECORD_PROGRAM_ID`
+const LOCALNET_ID: &str = "RecordLoca1net11111111111111111111111111111";
+
+fn decode(value: &str) -> [u8; 32] {
+    let bytes = bs58::decode(value.trim())
+        .into_vec()
+        .unwrap_or_else(|err| panic!("{PROGRAM_ID_VAR} is not base58: {err}"));
+    <[u8; 32]>::try_from(bytes.as_slice())
+        .unwrap_or_else(|_| panic!("{PROGRAM_ID_VAR} must be 32 bytes, got {}", bytes.len()))
+}
+
+fn main() {
+    println!("cargo:rerun-if-env-changed={PROGRAM_ID_VAR}");
+
+    let id = match env::var(PROGRAM_ID_VAR) {
+        Ok(value) => decode(&value),
+        Err(_) if env::var_os("CARGO_FEATURE_LOCALNET").is_some() => decode(LOCALNET_ID),
+        Err(_) => {
+            // deploying with the placeholder makes every instruction fail
+            if env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("solana") {
+                println!(
+                    "cargo:warning={PROGRAM_ID_VAR} is not set, building with the placeholder program id {PLACEHOLDER_ID}"
+                );
+            }
+            decode(PLACEHOLDER_ID)
+        }
+    };
+
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("program_id.rs");
+    fs::write(out, format!("{id:?}")).unwrap();
+}
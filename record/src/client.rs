@@ -0,0 +1,566 @@
+//! Instruction builders for off-chain clients, enabled with the `client`
+//! feature.
+//!
+//! Every builder returns an [`Instruction`] for the program at `program_id`,
+//! with the accounts in the order the processor expects them, see the README
+//! for the account lists. [`id`] is the address the crate was built for.
+
+use core::fmt;
+
+use pinocchio::program_error::ProgramError;
+use solana_instruction::{AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+
+use crate::{
+    error::RecordError,
+    instruction::{Comparison, RecordInstruction},
+    state::{RecordData, RecordSlot},
+    value::{Encoding, Value, ValueType},
+};
+
+/// Address of the record program, [`crate::ID`]
+pub fn id() -> Pubkey {
+    Pubkey::new_from_array(crate::ID)
+}
+
+/// Optional restrictions of a new record, see [`RecordInstruction::Initialize`]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RecordConfig {
+    /// Program that must own every source account
+    pub source_owner: Option<Pubkey>,
+
+    /// Mint required for token accounts, and the only mint accepted for
+    /// supply snapshots
+    pub token_mint: Option<Pubkey>,
+
+    /// Owner required for token accounts
+    pub token_owner: Option<Pubkey>,
+}
+
+/// Location of a value in a source account and how it is encoded
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SourceValue {
+    pub offset: u64,
+    pub value_type: ValueType,
+    pub encoding: Encoding,
+}
+
+impl SourceValue {
+    /// Little-endian u64 at `offset`, e.g. `64` for the amount of a token
+    /// account
+    pub fn u64(offset: u64) -> Self {
+        Self {
+            offset,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+        }
+    }
+}
+
+/// Size of a record account holding `slot_count` slots
+pub fn account_len(slot_count: u8) -> usize {
+    RecordData::account_len(slot_count)
+}
+
+/// Creates the account for a record with `slot_count` slots, owned by the
+/// record program. It must be followed by [`initialize`] in the same
+/// transaction, and `lamports` should be the rent exempt minimum for
+/// [`account_len`].
+pub fn create_account(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    record: &Pubkey,
+    slot_count: u8,
+    lamports: u64,
+) -> Instruction {
+    solana_system_interface::instruction::create_account(
+        payer,
+        record,
+        lamports,
+        account_len(slot_count) as u64,
+        program_id,
+    )
+}
+
+/// Address and bump seed of the record derived from `authority` and `seed`,
+/// see [`initialize_pda`]
+pub fn find_record_address(program_id: &Pubkey, authority: &Pubkey, seed: &[u8]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[RecordData::SEED_PREFIX, authority.as_ref(), seed],
+        program_id,
+    )
+}
+
+pub fn initialize(program_id: &Pubkey, record: &Pubkey, authority: &Pubkey) -> Instruction {
+    initialize_with_config(program_id, record, authority, &RecordConfig::default())
+}
+
+pub fn initialize_with_config(
+    program_id: &Pubkey,
+    record: &Pubkey,
+    authority: &Pubkey,
+    config: &RecordConfig,
+) -> Instruction {
+    build(
+        program_id,
+        RecordInstruction::Initialize {
+            source_owner: config.source_owner.map(|key| key.to_bytes()),
+            token_mint: config.token_mint.map(|key| key.to_bytes()),
+            token_owner: config.token_owner.map(|key| key.to_bytes()),
+        },
+        vec![
+            AccountMeta::new(*record, false),
+            AccountMeta::new_readonly(*authority, false),
+        ],
+    )
+}
+
+/// Creates and initializes the record at [`find_record_address`], the payer
+/// funds its rent. Fails with [`ProgramError::MaxSeedLengthExceeded`] for a
+/// seed longer than [`RecordData::MAX_SEED_LEN`]
+pub fn initialize_pda(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    payer: &Pubkey,
+    seed: &[u8],
+    slot_count: u8,
+    config: &RecordConfig,
+) -> Result<Instruction, ProgramError> {
+    if seed.len() > RecordData::MAX_SEED_LEN {
+        return Err(ProgramError::MaxSeedLengthExceeded);
+    }
+    let (record, bump) = find_record_address(program_id, authority, seed);
+    Ok(build(
+        program_id,
+        RecordInstruction::InitializePda {
+            slot_count,
+            seed: seed.to_vec(),
+            bump,
+            source_owner: config.source_owner.map(|key| key.to_bytes()),
+            token_mint: config.token_mint.map(|key| key.to_bytes()),
+            token_owner: config.token_owner.map(|key| key.to_bytes()),
+        },
+        vec![
+            AccountMeta::new(record, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(solana_system_interface::program::ID, false),
+        ],
+    ))
+}
+
+pub fn write_u64(
+    program_id: &Pubkey,
+    record: &Pubkey,
+    authority: &Pubkey,
+    source: &Pubkey,
+    slot: u8,
+    value: SourceValue,
+    pin_source: bool,
+) -> Instruction {
+    build(
+        program_id,
+        RecordInstruction::WriteU64 {
+            slot,
+            offset: value.offset,
+            value_type: value.value_type,
+            encoding: value.encoding,
+            pin_source,
+        },
+        write_accounts(record, authority, source),
+    )
+}
+
+pub fn check_add(
+    program_id: &Pubkey,
+    record: &Pubkey,
+    authority: &Pubkey,
+    source: &Pubkey,
+    slot: u8,
+    value: SourceValue,
+    addition: u64,
+) -> Instruction {
+    build(
+        program_id,
+        RecordInstruction::CheckAdd {
+            slot,
+            offset: value.offset,
+            value_type: value.value_type,
+            encoding: value.encoding,
+            addition,
+        },
+        check_accounts(record, authority, source),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn check_sub(
+    program_id: &Pubkey,
+    record: &Pubkey,
+    authority: &Pubkey,
+    source: &Pubkey,
+    slot: u8,
+    value: SourceValue,
+    min_decrease: u64,
+    max_decrease: u64,
+) -> Instruction {
+    build(
+        program_id,
+        RecordInstruction::CheckSub {
+            slot,
+            offset: value.offset,
+            value_type: value.value_type,
+            encoding: value.encoding,
+            min_decrease,
+            max_decrease,
+        },
+        check_accounts(record, authority, source),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn check_compare(
+    program_id: &Pubkey,
+    record: &Pubkey,
+    authority: &Pubkey,
+    source: &Pubkey,
+    slot: u8,
+    value: SourceValue,
+    op: Comparison,
+    operand: u64,
+) -> Instruction {
+    build(
+        program_id,
+        RecordInstruction::CheckCompare {
+            slot,
+            offset: value.offset,
+            value_type: value.value_type,
+            encoding: value.encoding,
+            op,
+            operand,
+        },
+        check_accounts(record, authority, source),
+    )
+}
+
+pub fn check_add_bps(
+    program_id: &Pubkey,
+    record: &Pubkey,
+    authority: &Pubkey,
+    source: &Pubkey,
+    slot: u8,
+    value: SourceValue,
+    bps: u16,
+) -> Instruction {
+    build(
+        program_id,
+        RecordInstruction::CheckAddBps {
+            slot,
+            offset: value.offset,
+            value_type: value.value_type,
+            encoding: value.encoding,
+            bps,
+        },
+        check_accounts(record, authority, source),
+    )
+}
+
+/// Compares the value of `account_a` against the value of `account_b`, which
+/// is read at `offset_b` with the type and encoding of `value_a`. Each account
+/// must be owned by `owner_a` and `owner_b` if set.
+#[allow(clippy::too_many_arguments)]
+pub fn check_compare_accounts(
+    program_id: &Pubkey,
+    account_a: &Pubkey,
+    account_b: &Pubkey,
+    value_a: SourceValue,
+    offset_b: u64,
+    op: Comparison,
+    operand: u64,
+    owner_a: Option<Pubkey>,
+    owner_b: Option<Pubkey>,
+) -> Instruction {
+    build(
+        program_id,
+        RecordInstruction::CheckCompareAccounts {
+            offset_a: value_a.offset,
+            offset_b,
+            value_type: value_a.value_type,
+            encoding: value_a.encoding,
+            op,
+            operand,
+            owner_a: owner_a.map(|key| key.to_bytes()),
+            owner_b: owner_b.map(|key| key.to_bytes()),
+        },
+        vec![
+            AccountMeta::new_readonly(*account_a, false),
+            AccountMeta::new_readonly(*account_b, false),
+        ],
+    )
+}
+
+pub fn write_token_amount(
+    program_id: &Pubkey,
+    record: &Pubkey,
+    authority: &Pubkey,
+    token_account: &Pubkey,
+    slot: u8,
+) -> Instruction {
+    build(
+        program_id,
+        RecordInstruction::WriteTokenAmount { slot },
+        write_accounts(record, authority, token_account),
+    )
+}
+
+pub fn check_token_add(
+    program_id: &Pubkey,
+    record: &Pubkey,
+    authority: &Pubkey,
+    token_account: &Pubkey,
+    slot: u8,
+    addition: u64,
+) -> Instruction {
+    build(
+        program_id,
+        RecordInstruction::CheckTokenAdd { slot, addition },
+        check_accounts(record, authority, token_account),
+    )
+}
+
+pub fn write_mint_supply(
+    program_id: &Pubkey,
+    record: &Pubkey,
+    authority: &Pubkey,
+    mint: &Pubkey,
+    slot: u8,
+) -> Instruction {
+    build(
+        program_id,
+        RecordInstruction::WriteMintSupply { slot },
+        write_accounts(record, authority, mint),
+    )
+}
+
+pub fn check_mint_supply_add(
+    program_id: &Pubkey,
+    record: &Pubkey,
+    authority: &Pubkey,
+    mint: &Pubkey,
+    slot: u8,
+    addition: u64,
+) -> Instruction {
+    build(
+        program_id,
+        RecordInstruction::CheckMintSupplyAdd { slot, addition },
+        check_accounts(record, authority, mint),
+    )
+}
+
+pub fn write_lamports(
+    program_id: &Pubkey,
+    record: &Pubkey,
+    authority: &Pubkey,
+    account: &Pubkey,
+    slot: u8,
+) -> Instruction {
+    build(
+        program_id,
+        RecordInstruction::WriteLamports { slot },
+        write_accounts(record, authority, account),
+    )
+}
+
+pub fn check_lamports_add(
+    program_id: &Pubkey,
+    record: &Pubkey,
+    authority: &Pubkey,
+    account: &Pubkey,
+    slot: u8,
+    addition: u64,
+) -> Instruction {
+    build(
+        program_id,
+        RecordInstruction::CheckLamportsAdd { slot, addition },
+        check_accounts(record, authority, account),
+    )
+}
+
+pub fn read_u64(program_id: &Pubkey, record: &Pubkey, slot: u8) -> Instruction {
+    build(
+        program_id,
+        RecordInstruction::ReadU64 { slot },
+        vec![AccountMeta::new_readonly(*record, false)],
+    )
+}
+
+pub fn set_authority(
+    program_id: &Pubkey,
+    record: &Pubkey,
+    authority: &Pubkey,
+    new_authority: &Pubkey,
+) -> Instruction {
+    build(
+        program_id,
+        RecordInstruction::SetAuthority,
+        vec![
+            AccountMeta::new(*record, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(*new_authority, false),
+        ],
+    )
+}
+
+pub fn close_account(
+    program_id: &Pubkey,
+    record: &Pubkey,
+    authority: &Pubkey,
+    destination: &Pubkey,
+) -> Instruction {
+    build(
+        program_id,
+        RecordInstruction::CloseAccount,
+        vec![
+            AccountMeta::new(*record, false),
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*destination, false),
+        ],
+    )
+}
+
+/// Decoded record account, built from the raw account data
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordAccount {
+    pub version: u8,
+    pub authority: Pubkey,
+    pub source_owner: Option<Pubkey>,
+    pub token_mint: Option<Pubkey>,
+    pub token_owner: Option<Pubkey>,
+    /// Bump seed, for records created with [`initialize_pda`]
+    pub bump: Option<u8>,
+    pub slots: Vec<RecordAccountSlot>,
+}
+
+/// Decoded slot of a [`RecordAccount`]. A slot that was never written reads
+/// as a u64 zero from the default pubkey.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RecordAccountSlot {
+    pub value_type: ValueType,
+    pub value: Value,
+    pub source: Pubkey,
+    /// Offset in the source account, [`RecordSlot::LAMPORTS_OFFSET`] for
+    /// lamport balances
+    pub offset: u64,
+    pub pinned: bool,
+}
+
+impl RecordAccount {
+    /// Decodes the data of a record account, failing on closed,
+    /// uninitialized or truncated records and unknown value types
+    pub fn unpack(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.first() == Some(&RecordData::CLOSED_VERSION) {
+            return Err(RecordError::RecordClosed.into());
+        }
+        let header = data
+            .get(..RecordData::WRITABLE_START_INDEX)
+            .and_then(|header| bytemuck::try_from_bytes::<RecordData>(header).ok())
+            .ok_or(ProgramError::InvalidAccountData)?;
+        if !header.is_initialized() {
+            return Err(ProgramError::UninitializedAccount);
+        }
+
+        let slots = (0..header.slot_count)
+            .map(|slot| {
+                let range = header.slot_range(slot).unwrap();
+                let slot = data
+                    .get(range)
+                    .and_then(|slot| bytemuck::try_from_bytes::<RecordSlot>(slot).ok())
+                    .ok_or(ProgramError::InvalidAccountData)?;
+                Ok(RecordAccountSlot {
+                    value_type: slot.value_type().ok_or(ProgramError::InvalidAccountData)?,
+                    value: slot.value().ok_or(ProgramError::InvalidAccountData)?,
+                    source: Pubkey::new_from_array(slot.source),
+                    offset: u64::from_le_bytes(slot.offset),
+                    pinned: slot.is_pinned(),
+                })
+            })
+            .collect::<Result<_, ProgramError>>()?;
+
+        Ok(Self {
+            version: header.version,
+            authority: Pubkey::new_from_array(header.authority),
+            source_owner: header.source_owner().copied().map(Pubkey::new_from_array),
+            token_mint: header.token_mint().copied().map(Pubkey::new_from_array),
+            token_owner: header.token_owner().copied().map(Pubkey::new_from_array),
+            bump: header.bump(),
+            slots,
+        })
+    }
+}
+
+impl fmt::Display for RecordAccount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Version: {}", self.version)?;
+        writeln!(f, "Authority: {}", self.authority)?;
+        for (name, key) in [
+            ("Source owner", &self.source_owner),
+            ("Token mint", &self.token_mint),
+            ("Token owner", &self.token_owner),
+        ] {
+            if let Some(key) = key {
+                writeln!(f, "{name}: {key}")?;
+            }
+        }
+        if let Some(bump) = self.bump {
+            writeln!(f, "Bump: {bump}")?;
+        }
+        write!(f, "Slots: {}", self.slots.len())?;
+        for (index, slot) in self.slots.iter().enumerate() {
+            write!(f, "\n  [{index}] {slot}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for RecordAccountSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} ", self.value_type, self.value)?;
+        if self.offset == RecordSlot::LAMPORTS_OFFSET {
+            write!(f, "lamports of {}", self.source)?;
+        } else {
+            write!(f, "from {} at offset {}", self.source, self.offset)?;
+        }
+        if self.pinned {
+            write!(f, ", pinned")?;
+        }
+        Ok(())
+    }
+}
+
+fn build(
+    program_id: &Pubkey,
+    instruction: RecordInstruction,
+    accounts: Vec<AccountMeta>,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: instruction.pack(),
+    }
+}
+
+fn write_accounts(record: &Pubkey, authority: &Pubkey, source: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(*record, false),
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*source, false),
+    ]
+}
+
+fn check_accounts(record: &Pubkey, authority: &Pubkey, source: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(*record, false),
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new_readonly(*source, false),
+    ]
+}
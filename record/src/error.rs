@@ -7,6 +7,63 @@ pub enum RecordError {
 
     /// Calculation overflow
     Overflow,
+
+    /// Slot index is outside of the record account
+    InvalidSlot,
+
+    /// Source value decreased by less than the required minimum
+    DecreaseBelowMinimum,
+
+    /// Source value decreased by more than the allowed maximum
+    DecreaseAboveMaximum,
+
+    /// Source value does not satisfy the requested comparison
+    ComparisonFailed,
+
+    /// Value type does not match the type recorded in the slot
+    ValueTypeMismatch,
+
+    /// Basis points calculation overflow
+    BpsOverflow,
+
+    /// Source account or offset differs from the one pinned at write time
+    SourceMismatch,
+
+    /// Source account is not owned by the program required by the record
+    IncorrectSourceOwner,
+
+    /// Source account is not an SPL Token or Token-2022 account
+    InvalidTokenAccount,
+
+    /// Token account is not initialized or is frozen
+    TokenAccountNotInitialized,
+
+    /// Token account mint differs from the one required by the record
+    TokenMintMismatch,
+
+    /// Token account owner differs from the one required by the record
+    TokenOwnerMismatch,
+
+    /// Source account is not an initialized SPL Token or Token-2022 mint
+    InvalidMint,
+
+    /// Record account is not owned by the record program
+    IncorrectRecordOwner,
+
+    /// Record account was closed and can not be used again
+    RecordClosed,
+
+    /// Offset is outside of the source account data
+    OffsetOutOfBounds,
+
+    /// Value at the offset extends past the end of the source account data
+    SourceTooSmall,
+
+    /// Account data or lamports are already borrowed
+    AccountBorrowFailed,
+
+    /// Source value increased by less than the required amount
+    InsufficientIncrease,
 }
 impl From<RecordError> for pinocchio::program_error::ProgramError {
     fn from(e: RecordError) -> Self {
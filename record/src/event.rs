@@ -0,0 +1,158 @@
+use pinocchio::{log::sol_log_data, program_error::ProgramError, pubkey::Pubkey};
+
+use crate::{
+    instruction::Unpacker,
+    value::{Value, ValueType},
+};
+
+/// Version of the event encoding, the first byte of every event
+pub const EVENT_VERSION: u8 = 1;
+
+/// Error decoding a [`RecordEvent`] off-chain, the program never returns it
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EventError {
+    /// Log data does not start with a known event version
+    UnknownVersion,
+
+    /// Log data is not a record event, or is truncated
+    InvalidData,
+}
+
+/// State change of a record account, logged with `sol_log_data` as a single
+/// `Program data:` entry.
+///
+/// Events are encoded as the version byte, a kind byte and the fields in
+/// order, pubkeys as 32 bytes and integers little-endian.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RecordEvent {
+    Initialize {
+        record: Pubkey,
+        authority: Pubkey,
+        slot_count: u8,
+    },
+    Write {
+        record: Pubkey,
+        slot: u8,
+        value_type: ValueType,
+        value: Value,
+        source: Pubkey,
+        offset: u64,
+        pinned: bool,
+    },
+    SetAuthority {
+        record: Pubkey,
+        old_authority: Pubkey,
+        new_authority: Pubkey,
+    },
+    Close {
+        record: Pubkey,
+        destination: Pubkey,
+        lamports: u64,
+    },
+}
+
+impl RecordEvent {
+    pub fn log(&self) {
+        sol_log_data(&[&self.pack()]);
+    }
+
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = vec![EVENT_VERSION];
+        match self {
+            Self::Initialize {
+                record,
+                authority,
+                slot_count,
+            } => {
+                buf.push(0);
+                buf.extend_from_slice(record);
+                buf.extend_from_slice(authority);
+                buf.push(*slot_count);
+            }
+            Self::Write {
+                record,
+                slot,
+                value_type,
+                value,
+                source,
+                offset,
+                pinned,
+            } => {
+                buf.push(1);
+                buf.extend_from_slice(record);
+                buf.push(*slot);
+                buf.push(*value_type as u8);
+                buf.extend_from_slice(&value.encode());
+                buf.extend_from_slice(source);
+                buf.extend_from_slice(&offset.to_le_bytes());
+                buf.push(*pinned as u8);
+            }
+            Self::SetAuthority {
+                record,
+                old_authority,
+                new_authority,
+            } => {
+                buf.push(2);
+                buf.extend_from_slice(record);
+                buf.extend_from_slice(old_authority);
+                buf.extend_from_slice(new_authority);
+            }
+            Self::Close {
+                record,
+                destination,
+                lamports,
+            } => {
+                buf.push(3);
+                buf.extend_from_slice(record);
+                buf.extend_from_slice(destination);
+                buf.extend_from_slice(&lamports.to_le_bytes());
+            }
+        }
+        buf
+    }
+
+    /// Decodes an event from the data of a `Program data:` log entry
+    pub fn unpack(data: &[u8]) -> Result<Self, EventError> {
+        let (&version, rest) = data.split_first().ok_or(EventError::InvalidData)?;
+        if version != EVENT_VERSION {
+            return Err(EventError::UnknownVersion);
+        }
+        Self::unpack_fields(&mut Unpacker::new(rest)).map_err(|_| EventError::InvalidData)
+    }
+
+    fn unpack_fields(rest: &mut Unpacker) -> Result<Self, ProgramError> {
+        Ok(match rest.u8()? {
+            0 => Self::Initialize {
+                record: rest.pubkey()?,
+                authority: rest.pubkey()?,
+                slot_count: rest.u8()?,
+            },
+            1 => {
+                let record = rest.pubkey()?;
+                let slot = rest.u8()?;
+                let value_type = rest.value_type()?;
+                let value = value_type.decode(rest.u128()?.to_le_bytes());
+                Self::Write {
+                    record,
+                    slot,
+                    value_type,
+                    value,
+                    source: rest.pubkey()?,
+                    offset: rest.u64()?,
+                    pinned: rest.bool()?,
+                }
+            }
+            2 => Self::SetAuthority {
+                record: rest.pubkey()?,
+                old_authority: rest.pubkey()?,
+                new_authority: rest.pubkey()?,
+            },
+            3 => Self::Close {
+                record: rest.pubkey()?,
+                destination: rest.pubkey()?,
+                lamports: rest.u64()?,
+            },
+            _ => return Err(ProgramError::InvalidInstructionData),
+        })
+    }
+}
@@ -1,44 +1,389 @@
-use {pinocchio::program_error::ProgramError, std::mem::size_of};
+use {
+    crate::{
+        error::RecordError,
+        state::RecordData,
+        value::{BitField, Encoding, Value, ValueType},
+    },
+    num_traits::FromPrimitive,
+    pinocchio::{program_error::ProgramError, pubkey::Pubkey},
+    std::mem::size_of,
+};
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum RecordInstruction {
-    Initialize,
-    WriteU64 { offset: u64 },
-    CheckAdd { offset: u64, addition: u64 },
+    Initialize {
+        source_owner: Option<Pubkey>,
+        token_mint: Option<Pubkey>,
+        token_owner: Option<Pubkey>,
+    },
+    WriteU64 {
+        slot: u8,
+        offset: u64,
+        value_type: ValueType,
+        encoding: Encoding,
+        pin_source: bool,
+    },
+    CheckAdd {
+        slot: u8,
+        offset: u64,
+        value_type: ValueType,
+        encoding: Encoding,
+        addition: u64,
+    },
     SetAuthority,
     CloseAccount,
+    CheckSub {
+        slot: u8,
+        offset: u64,
+        value_type: ValueType,
+        encoding: Encoding,
+        min_decrease: u64,
+        max_decrease: u64,
+    },
+    CheckCompare {
+        slot: u8,
+        offset: u64,
+        value_type: ValueType,
+        encoding: Encoding,
+        op: Comparison,
+        operand: u64,
+    },
+    CheckAddBps {
+        slot: u8,
+        offset: u64,
+        value_type: ValueType,
+        encoding: Encoding,
+        bps: u16,
+    },
+    CheckCompareAccounts {
+        offset_a: u64,
+        offset_b: u64,
+        value_type: ValueType,
+        encoding: Encoding,
+        op: Comparison,
+        operand: u64,
+        /// Program that must own the first account
+        owner_a: Option<Pubkey>,
+        /// Program that must own the second account
+        owner_b: Option<Pubkey>,
+    },
+    WriteTokenAmount {
+        slot: u8,
+    },
+    CheckTokenAdd {
+        slot: u8,
+        addition: u64,
+    },
+    WriteMintSupply {
+        slot: u8,
+    },
+    CheckMintSupplyAdd {
+        slot: u8,
+        addition: u64,
+    },
+    WriteLamports {
+        slot: u8,
+    },
+    CheckLamportsAdd {
+        slot: u8,
+        addition: u64,
+    },
+    InitializePda {
+        slot_count: u8,
+        seed: Vec<u8>,
+        bump: u8,
+        source_owner: Option<Pubkey>,
+        token_mint: Option<Pubkey>,
+        token_owner: Option<Pubkey>,
+    },
+    ReadU64 {
+        slot: u8,
+    },
+}
+
+/// Comparison between the current source value and the recorded value
+/// shifted by an operand, used by [`RecordInstruction::CheckCompare`].
+///
+/// [`RecordInstruction::CheckCompareAccounts`] compares the value of the first
+/// account against the value of the second one in the same way.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    /// Current value lies within `[recorded + operand, recorded + upper]`
+    Range {
+        upper: u64,
+    },
+}
+
+impl Comparison {
+    /// Compares `current` against `recorded + operand`
+    pub fn evaluate(
+        &self,
+        current: Value,
+        recorded: Value,
+        operand: u64,
+    ) -> Result<bool, RecordError> {
+        let target = recorded.checked_add(operand).ok_or(RecordError::Overflow)?;
+        Ok(match self {
+            Self::Eq => current == target,
+            Self::Ne => current != target,
+            Self::Lt => current < target,
+            Self::Le => current <= target,
+            Self::Gt => current > target,
+            Self::Ge => current >= target,
+            Self::Range { upper } => {
+                let upper = recorded.checked_add(*upper).ok_or(RecordError::Overflow)?;
+                (target..=upper).contains(&current)
+            }
+        })
+    }
+
+    fn unpack(input: &mut Unpacker) -> Result<Self, ProgramError> {
+        Ok(match input.u8()? {
+            0 => Self::Eq,
+            1 => Self::Ne,
+            2 => Self::Lt,
+            3 => Self::Le,
+            4 => Self::Gt,
+            5 => Self::Ge,
+            6 => Self::Range {
+                upper: input.u64()?,
+            },
+            _ => return Err(ProgramError::InvalidInstructionData),
+        })
+    }
+
+    fn pack(&self, buf: &mut Vec<u8>) {
+        buf.push(self.code());
+        if let Self::Range { upper } = self {
+            buf.extend_from_slice(&upper.to_le_bytes());
+        }
+    }
+
+    fn code(&self) -> u8 {
+        match self {
+            Self::Eq => 0,
+            Self::Ne => 1,
+            Self::Lt => 2,
+            Self::Le => 3,
+            Self::Gt => 4,
+            Self::Ge => 5,
+            Self::Range { .. } => 6,
+        }
+    }
+}
+
+/// Reads instruction fields in order, failing on truncated input
+pub(crate) struct Unpacker<'a> {
+    input: &'a [u8],
+}
+
+impl<'a> Unpacker<'a> {
+    pub(crate) fn new(input: &'a [u8]) -> Self {
+        Self { input }
+    }
+    fn take(&mut self, len: usize) -> Result<&[u8], ProgramError> {
+        if self.input.len() < len {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let (taken, rest) = self.input.split_at(len);
+        self.input = rest;
+        Ok(taken)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, ProgramError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn bool(&mut self) -> Result<bool, ProgramError> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+
+    fn u16(&mut self) -> Result<u16, ProgramError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, ProgramError> {
+        let bytes = self.take(8)?;
+        Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    /// Length-prefixed seed of at most [`RecordData::MAX_SEED_LEN`] bytes
+    fn seed(&mut self) -> Result<Vec<u8>, ProgramError> {
+        let len = self.u8()? as usize;
+        if len > RecordData::MAX_SEED_LEN {
+            return Err(ProgramError::MaxSeedLengthExceeded);
+        }
+        Ok(self.take(len)?.to_vec())
+    }
+
+    pub(crate) fn u128(&mut self) -> Result<u128, ProgramError> {
+        let bytes = self.take(16)?;
+        Ok(u128::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn option_pubkey(&mut self) -> Result<Option<Pubkey>, ProgramError> {
+        if !self.bool()? {
+            return Ok(None);
+        }
+        Ok(Some(self.pubkey()?))
+    }
+
+    pub(crate) fn pubkey(&mut self) -> Result<Pubkey, ProgramError> {
+        Ok(self.take(32)?.try_into().unwrap())
+    }
+
+    pub(crate) fn value_type(&mut self) -> Result<ValueType, ProgramError> {
+        ValueType::from_u8(self.u8()?).ok_or(ProgramError::InvalidInstructionData)
+    }
+
+    fn encoding(&mut self) -> Result<Encoding, ProgramError> {
+        let flags = self.u8()?;
+        if flags & !(ENCODING_BIG_ENDIAN | ENCODING_BIT_FIELD) != 0 {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let bit_field = if flags & ENCODING_BIT_FIELD != 0 {
+            let mask = self.u128()?;
+            let shift = self.u8()?;
+            if shift >= 128 {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+            Some(BitField { mask, shift })
+        } else {
+            None
+        };
+        Ok(Encoding {
+            big_endian: flags & ENCODING_BIG_ENDIAN != 0,
+            bit_field,
+        })
+    }
+}
+
+fn pack_option_pubkey(value: &Option<Pubkey>, buf: &mut Vec<u8>) {
+    match value {
+        Some(pubkey) => {
+            buf.push(1);
+            buf.extend_from_slice(pubkey);
+        }
+        None => buf.push(0),
+    }
+}
+
+const ENCODING_BIG_ENDIAN: u8 = 1;
+const ENCODING_BIT_FIELD: u8 = 1 << 1;
+
+/// Packs an [`Encoding`] as a flags byte, followed by mask and shift of the
+/// bit field if there is one
+fn pack_encoding(encoding: &Encoding, buf: &mut Vec<u8>) {
+    let mut flags = 0;
+    if encoding.big_endian {
+        flags |= ENCODING_BIG_ENDIAN;
+    }
+    if encoding.bit_field.is_some() {
+        flags |= ENCODING_BIT_FIELD;
+    }
+    buf.push(flags);
+    if let Some(field) = encoding.bit_field {
+        buf.extend_from_slice(&field.mask.to_le_bytes());
+        buf.push(field.shift);
+    }
 }
 
 impl RecordInstruction {
     pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
-        const U64_BYTES: usize = 8;
-
         let (&tag, rest) = input
             .split_first()
             .ok_or(ProgramError::InvalidInstructionData)?;
+        let rest = &mut Unpacker::new(rest);
         Ok(match tag {
-            0 => Self::Initialize,
+            0 => Self::Initialize {
+                source_owner: rest.option_pubkey()?,
+                token_mint: rest.option_pubkey()?,
+                token_owner: rest.option_pubkey()?,
+            },
             1 => Self::WriteU64 {
-                offset: rest
-                    .get(..U64_BYTES)
-                    .and_then(|slice| slice.try_into().ok())
-                    .map(u64::from_le_bytes)
-                    .ok_or(ProgramError::InvalidInstructionData)?,
+                slot: rest.u8()?,
+                offset: rest.u64()?,
+                value_type: rest.value_type()?,
+                encoding: rest.encoding()?,
+                pin_source: rest.bool()?,
             },
             2 => Self::CheckAdd {
-                offset: rest
-                    .get(..U64_BYTES)
-                    .and_then(|slice| slice.try_into().ok())
-                    .map(u64::from_le_bytes)
-                    .ok_or(ProgramError::InvalidInstructionData)?,
-                addition: rest[U64_BYTES..]
-                    .get(..U64_BYTES)
-                    .and_then(|slice| slice.try_into().ok())
-                    .map(u64::from_le_bytes)
-                    .ok_or(ProgramError::InvalidInstructionData)?,
+                slot: rest.u8()?,
+                offset: rest.u64()?,
+                value_type: rest.value_type()?,
+                encoding: rest.encoding()?,
+                addition: rest.u64()?,
             },
             3 => Self::SetAuthority,
             4 => Self::CloseAccount,
+            5 => Self::CheckSub {
+                slot: rest.u8()?,
+                offset: rest.u64()?,
+                value_type: rest.value_type()?,
+                encoding: rest.encoding()?,
+                min_decrease: rest.u64()?,
+                max_decrease: rest.u64()?,
+            },
+            6 => Self::CheckCompare {
+                slot: rest.u8()?,
+                offset: rest.u64()?,
+                value_type: rest.value_type()?,
+                encoding: rest.encoding()?,
+                op: Comparison::unpack(rest)?,
+                operand: rest.u64()?,
+            },
+            7 => Self::CheckAddBps {
+                slot: rest.u8()?,
+                offset: rest.u64()?,
+                value_type: rest.value_type()?,
+                encoding: rest.encoding()?,
+                bps: rest.u16()?,
+            },
+            8 => Self::CheckCompareAccounts {
+                offset_a: rest.u64()?,
+                offset_b: rest.u64()?,
+                value_type: rest.value_type()?,
+                encoding: rest.encoding()?,
+                op: Comparison::unpack(rest)?,
+                operand: rest.u64()?,
+                owner_a: rest.option_pubkey()?,
+                owner_b: rest.option_pubkey()?,
+            },
+            9 => Self::WriteTokenAmount { slot: rest.u8()? },
+            10 => Self::CheckTokenAdd {
+                slot: rest.u8()?,
+                addition: rest.u64()?,
+            },
+            11 => Self::WriteMintSupply { slot: rest.u8()? },
+            12 => Self::CheckMintSupplyAdd {
+                slot: rest.u8()?,
+                addition: rest.u64()?,
+            },
+            13 => Self::WriteLamports { slot: rest.u8()? },
+            14 => Self::CheckLamportsAdd {
+                slot: rest.u8()?,
+                addition: rest.u64()?,
+            },
+            15 => Self::InitializePda {
+                slot_count: rest.u8()?,
+                seed: rest.seed()?,
+                bump: rest.u8()?,
+                source_owner: rest.option_pubkey()?,
+                token_mint: rest.option_pubkey()?,
+                token_owner: rest.option_pubkey()?,
+            },
+            16 => Self::ReadU64 { slot: rest.u8()? },
             _ => return Err(ProgramError::InvalidInstructionData),
         })
     }
@@ -47,18 +392,160 @@ impl RecordInstruction {
     pub fn pack(&self) -> Vec<u8> {
         let mut buf = Vec::with_capacity(size_of::<Self>());
         match self {
-            Self::Initialize => buf.push(0),
-            Self::WriteU64 { offset } => {
+            Self::Initialize {
+                source_owner,
+                token_mint,
+                token_owner,
+            } => {
+                buf.push(0);
+                pack_option_pubkey(source_owner, &mut buf);
+                pack_option_pubkey(token_mint, &mut buf);
+                pack_option_pubkey(token_owner, &mut buf);
+            }
+            Self::WriteU64 {
+                slot,
+                offset,
+                value_type,
+                encoding,
+                pin_source,
+            } => {
                 buf.push(1);
+                buf.push(*slot);
                 buf.extend_from_slice(&offset.to_le_bytes());
+                buf.push(*value_type as u8);
+                pack_encoding(encoding, &mut buf);
+                buf.push(*pin_source as u8);
             }
-            Self::CheckAdd { offset, addition } => {
+            Self::CheckAdd {
+                slot,
+                offset,
+                value_type,
+                encoding,
+                addition,
+            } => {
                 buf.push(2);
+                buf.push(*slot);
                 buf.extend_from_slice(&offset.to_le_bytes());
+                buf.push(*value_type as u8);
+                pack_encoding(encoding, &mut buf);
                 buf.extend_from_slice(&addition.to_le_bytes());
             }
             Self::SetAuthority => buf.push(3),
             Self::CloseAccount => buf.push(4),
+            Self::CheckSub {
+                slot,
+                offset,
+                value_type,
+                encoding,
+                min_decrease,
+                max_decrease,
+            } => {
+                buf.push(5);
+                buf.push(*slot);
+                buf.extend_from_slice(&offset.to_le_bytes());
+                buf.push(*value_type as u8);
+                pack_encoding(encoding, &mut buf);
+                buf.extend_from_slice(&min_decrease.to_le_bytes());
+                buf.extend_from_slice(&max_decrease.to_le_bytes());
+            }
+            Self::CheckCompare {
+                slot,
+                offset,
+                value_type,
+                encoding,
+                op,
+                operand,
+            } => {
+                buf.push(6);
+                buf.push(*slot);
+                buf.extend_from_slice(&offset.to_le_bytes());
+                buf.push(*value_type as u8);
+                pack_encoding(encoding, &mut buf);
+                op.pack(&mut buf);
+                buf.extend_from_slice(&operand.to_le_bytes());
+            }
+            Self::CheckAddBps {
+                slot,
+                offset,
+                value_type,
+                encoding,
+                bps,
+            } => {
+                buf.push(7);
+                buf.push(*slot);
+                buf.extend_from_slice(&offset.to_le_bytes());
+                buf.push(*value_type as u8);
+                pack_encoding(encoding, &mut buf);
+                buf.extend_from_slice(&bps.to_le_bytes());
+            }
+            Self::CheckCompareAccounts {
+                offset_a,
+                offset_b,
+                value_type,
+                encoding,
+                op,
+                operand,
+                owner_a,
+                owner_b,
+            } => {
+                buf.push(8);
+                buf.extend_from_slice(&offset_a.to_le_bytes());
+                buf.extend_from_slice(&offset_b.to_le_bytes());
+                buf.push(*value_type as u8);
+                pack_encoding(encoding, &mut buf);
+                op.pack(&mut buf);
+                buf.extend_from_slice(&operand.to_le_bytes());
+                pack_option_pubkey(owner_a, &mut buf);
+                pack_option_pubkey(owner_b, &mut buf);
+            }
+            Self::WriteTokenAmount { slot } => {
+                buf.push(9);
+                buf.push(*slot);
+            }
+            Self::CheckTokenAdd { slot, addition } => {
+                buf.push(10);
+                buf.push(*slot);
+                buf.extend_from_slice(&addition.to_le_bytes());
+            }
+            Self::WriteMintSupply { slot } => {
+                buf.push(11);
+                buf.push(*slot);
+            }
+            Self::CheckMintSupplyAdd { slot, addition } => {
+                buf.push(12);
+                buf.push(*slot);
+                buf.extend_from_slice(&addition.to_le_bytes());
+            }
+            Self::WriteLamports { slot } => {
+                buf.push(13);
+                buf.push(*slot);
+            }
+            Self::CheckLamportsAdd { slot, addition } => {
+                buf.push(14);
+                buf.push(*slot);
+                buf.extend_from_slice(&addition.to_le_bytes());
+            }
+            Self::InitializePda {
+                slot_count,
+                seed,
+                bump,
+                source_owner,
+                token_mint,
+                token_owner,
+            } => {
+                buf.push(15);
+                buf.push(*slot_count);
+                buf.push(seed.len() as u8);
+                buf.extend_from_slice(seed);
+                buf.push(*bump);
+                pack_option_pubkey(source_owner, &mut buf);
+                pack_option_pubkey(token_mint, &mut buf);
+                pack_option_pubkey(token_owner, &mut buf);
+            }
+            Self::ReadU64 { slot } => {
+                buf.push(16);
+                buf.push(*slot);
+            }
         };
         buf
     }
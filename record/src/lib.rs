@@ -3,18 +3,24 @@ use pinocchio::{
     pubkey::Pubkey, ProgramResult,
 };
 
+#[cfg(feature = "client")]
+pub mod client;
 pub mod error;
+pub mod event;
 pub mod instruction;
 pub mod processor;
 pub mod state;
+pub mod system;
+pub mod token;
+pub mod value;
 
 pub use pinocchio;
 
-// TODO: Replace with your deployed program ID
-// To get your program ID after deployment, run: solana address -k target/deploy/record-keypair.json
-pub const ID: Pubkey = [
-    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-];
+/// Address the program is deployed at, set at build time from the base58
+/// `RECORD_PROGRAM_ID` environment variable. Builds without it use
+/// `RecordLoca1net11111111111111111111111111111` with the `localnet` feature
+/// and the placeholder `RecordP1aceho1der11111111111111111111111111` otherwise.
+pub const ID: Pubkey = include!(concat!(env!("OUT_DIR"), "/program_id.rs"));
 
 fn process_instruction(
     program_id: &Pubkey,
@@ -1,9 +1,78 @@
+use core::mem::{offset_of, size_of};
+
 use pinocchio::{
-    account_info::AccountInfo, get_account_info, program_error::ProgramError, pubkey::Pubkey,
+    account_info::{AccountInfo, Ref, RefMut},
+    get_account_info,
+    instruction::{Seed, Signer},
+    msg,
+    program::set_return_data,
+    program_error::ProgramError,
+    pubkey::{create_program_address, Pubkey},
+    sysvars::{rent::Rent, Sysvar},
     ProgramResult,
 };
 
-use crate::{error::RecordError, instruction::RecordInstruction, state::RecordData};
+use crate::{
+    error::RecordError,
+    event::RecordEvent,
+    instruction::{Comparison, RecordInstruction},
+    state::{RecordData, RecordSlot},
+    system,
+    token::{self, TokenAccount, TokenMint},
+    value::{Encoding, Value, ValueType},
+};
+
+fn borrow_data(account_info: &AccountInfo) -> Result<Ref<'_, [u8]>, ProgramError> {
+    account_info
+        .try_borrow_data()
+        .map_err(|_| RecordError::AccountBorrowFailed.into())
+}
+
+fn borrow_mut_data(account_info: &AccountInfo) -> Result<RefMut<'_, [u8]>, ProgramError> {
+    account_info
+        .try_borrow_mut_data()
+        .map_err(|_| RecordError::AccountBorrowFailed.into())
+}
+
+fn borrow_mut_lamports(account_info: &AccountInfo) -> Result<RefMut<'_, u64>, ProgramError> {
+    account_info
+        .try_borrow_mut_lamports()
+        .map_err(|_| RecordError::AccountBorrowFailed.into())
+}
+
+/// Reads a value of `value_type` at `offset` of the source account
+fn read_source(
+    read_account_info: &AccountInfo,
+    offset: u64,
+    value_type: ValueType,
+    encoding: &Encoding,
+) -> Result<Value, ProgramError> {
+    let data = borrow_data(read_account_info)?;
+    let offset = usize::try_from(offset)
+        .ok()
+        .filter(|offset| *offset < data.len())
+        .ok_or(RecordError::OffsetOutOfBounds)?;
+    value_type
+        .read(&data, offset, encoding)
+        .ok_or(RecordError::SourceTooSmall.into())
+}
+
+/// Gets the record account, the first account of every instruction that reads
+/// or writes record data, checking that it is owned by the record program and
+/// was not closed
+fn record_info<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo],
+) -> Result<&'a AccountInfo, ProgramError> {
+    let data_info = get_account_info!(accounts, 0);
+    if data_info.owner() != program_id {
+        return Err(RecordError::IncorrectRecordOwner.into());
+    }
+    if borrow_data(data_info)?.first() == Some(&RecordData::CLOSED_VERSION) {
+        return Err(RecordError::RecordClosed.into());
+    }
+    Ok(data_info)
+}
 
 fn check_authority(authority_info: &AccountInfo, expected_authority: &Pubkey) -> ProgramResult {
     if expected_authority != authority_info.key() {
@@ -15,148 +84,714 @@ fn check_authority(authority_info: &AccountInfo, expected_authority: &Pubkey) ->
     Ok(())
 }
 
+/// Authority of an initialized record, of the current or the legacy layout
+fn record_authority(raw_data: &mut [u8]) -> Result<&mut Pubkey, ProgramError> {
+    let header_len = match raw_data.first() {
+        Some(&RecordData::CURRENT_VERSION) => RecordData::WRITABLE_START_INDEX,
+        Some(&RecordData::LEGACY_VERSION) => RecordData::LEGACY_HEADER_LEN,
+        Some(_) => return Err(ProgramError::UninitializedAccount),
+        None => return Err(ProgramError::InvalidAccountData),
+    };
+    if raw_data.len() < header_len {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let start = offset_of!(RecordData, authority);
+    <&mut Pubkey>::try_from(&mut raw_data[start..start + size_of::<Pubkey>()])
+        .map_err(|_| ProgramError::InvalidAccountData)
+}
+
+/// Loads the header and `slot` of an initialized record account
+fn read_slot(data_info: &AccountInfo, slot: u8) -> Result<(RecordData, RecordSlot), ProgramError> {
+    let raw_data = &borrow_data(data_info)?;
+    if raw_data.len() < RecordData::WRITABLE_START_INDEX {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let account_data =
+        bytemuck::try_from_bytes::<RecordData>(&raw_data[..RecordData::WRITABLE_START_INDEX])
+            .map_err(|_| ProgramError::InvalidArgument)?;
+
+    if !account_data.is_initialized() {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    let slot_range = account_data
+        .slot_range(slot)
+        .ok_or(RecordError::InvalidSlot)?;
+    let slot_data = raw_data.get(slot_range).ok_or(RecordError::InvalidSlot)?;
+
+    Ok((
+        *account_data,
+        *bytemuck::from_bytes::<RecordSlot>(slot_data),
+    ))
+}
+
+/// Loads the header and `slot` of the record account, checking the authority
+/// and that the source account may be read into the record
+fn load_slot(
+    data_info: &AccountInfo,
+    authority_info: &AccountInfo,
+    read_account_info: &AccountInfo,
+    slot: u8,
+) -> Result<(RecordData, RecordSlot), ProgramError> {
+    let (account_data, record_slot) = read_slot(data_info, slot)?;
+
+    check_authority(authority_info, &account_data.authority)?;
+    if !account_data.accepts_source_owner(read_account_info.owner()) {
+        return Err(RecordError::IncorrectSourceOwner.into());
+    }
+
+    Ok((account_data, record_slot))
+}
+
+/// Stores `record_slot` into `slot` of the record account
+fn store_slot(
+    data_info: &AccountInfo,
+    account_data: &RecordData,
+    slot: u8,
+    record_slot: &RecordSlot,
+) -> ProgramResult {
+    let raw_data = &mut borrow_mut_data(data_info)?;
+    let slot_range = account_data
+        .slot_range(slot)
+        .ok_or(RecordError::InvalidSlot)?;
+    raw_data
+        .get_mut(slot_range)
+        .ok_or(RecordError::InvalidSlot)?
+        .copy_from_slice(bytemuck::bytes_of(record_slot));
+
+    let value_type = record_slot
+        .value_type()
+        .ok_or(RecordError::ValueTypeMismatch)?;
+    RecordEvent::Write {
+        record: *data_info.key(),
+        slot,
+        value_type,
+        value: value_type.decode(record_slot.value),
+        source: record_slot.source,
+        offset: u64::from_le_bytes(record_slot.offset),
+        pinned: record_slot.is_pinned(),
+    }
+    .log();
+
+    Ok(())
+}
+
+/// Loads the value recorded in `slot` of the record account and the current
+/// value at `offset` of the source account, for the check instructions
+fn load_check_values(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    slot: u8,
+    offset: u64,
+    value_type: ValueType,
+    encoding: &Encoding,
+) -> Result<(Value, Value), ProgramError> {
+    let data_info = record_info(program_id, accounts)?;
+    let authority_info = get_account_info!(accounts, 1);
+    let read_account_info = get_account_info!(accounts, 2);
+
+    let (_, record_slot) = load_slot(data_info, authority_info, read_account_info, slot)?;
+    if record_slot.value_type() != Some(value_type) {
+        return Err(RecordError::ValueTypeMismatch.into());
+    }
+    if !record_slot.accepts_source(read_account_info.key(), offset) {
+        return Err(RecordError::SourceMismatch.into());
+    }
+    let old_value = value_type.decode(record_slot.value);
+
+    let new_value = read_source(read_account_info, offset, value_type, encoding)?;
+
+    Ok((old_value, new_value))
+}
+
+/// Parses the source account as a token account, checking it against the
+/// mint and owner required by the record
+fn load_token_account(
+    account_data: &RecordData,
+    read_account_info: &AccountInfo,
+) -> Result<TokenAccount, ProgramError> {
+    let token_account =
+        TokenAccount::unpack(read_account_info.owner(), &borrow_data(read_account_info)?)?;
+
+    if let Some(mint) = account_data.token_mint() {
+        if mint != &token_account.mint {
+            return Err(RecordError::TokenMintMismatch.into());
+        }
+    }
+    if let Some(owner) = account_data.token_owner() {
+        if owner != &token_account.owner {
+            return Err(RecordError::TokenOwnerMismatch.into());
+        }
+    }
+
+    Ok(token_account)
+}
+
+/// Parses `read_account_info` as a token mint, checking it against the
+/// `token_mint` of the record
+fn load_token_mint(
+    account_data: &RecordData,
+    read_account_info: &AccountInfo,
+) -> Result<TokenMint, ProgramError> {
+    let token_mint =
+        TokenMint::unpack(read_account_info.owner(), &borrow_data(read_account_info)?)?;
+
+    if let Some(mint) = account_data.token_mint() {
+        if mint != read_account_info.key() {
+            return Err(RecordError::TokenMintMismatch.into());
+        }
+    }
+
+    Ok(token_mint)
+}
+
+/// Checks that a u64 amount read from `source` at `offset` grew by at
+/// least `addition` over the amount recorded in `record_slot`
+fn check_u64_increase(
+    record_slot: &RecordSlot,
+    source: &Pubkey,
+    offset: u64,
+    amount: u64,
+    addition: u64,
+) -> ProgramResult {
+    if record_slot.value_type() != Some(ValueType::U64) {
+        return Err(RecordError::ValueTypeMismatch.into());
+    }
+    if !record_slot.accepts_source(source, offset) {
+        return Err(RecordError::SourceMismatch.into());
+    }
+
+    let old_value = ValueType::U64.decode(record_slot.value);
+    check_increase(
+        ValueType::U64,
+        Value::Unsigned(amount as u128),
+        old_value,
+        addition,
+    )
+}
+
+/// Checks that `current` is at least `recorded` plus `addition`, returning
+/// the observed increase as a little-endian u128 in the return data
+fn check_increase(
+    value_type: ValueType,
+    current: Value,
+    recorded: Value,
+    addition: u64,
+) -> ProgramResult {
+    // a required value outside of the type could never be observed
+    let required = value_type
+        .checked_add(recorded, addition)
+        .ok_or(RecordError::Overflow)?;
+    if current < required {
+        return Err(insufficient_increase(current, required));
+    }
+
+    set_return_data(&current.increase_from(&recorded).to_le_bytes());
+    Ok(())
+}
+
+/// Logs the observed and required values of a failed increase check
+fn insufficient_increase(current: Value, required: Value) -> ProgramError {
+    msg!(&format!(
+        "Insufficient increase: observed {current}, required at least {required}"
+    ));
+    RecordError::InsufficientIncrease.into()
+}
+
+/// Logs the operands of a failed comparison
+fn comparison_failed(
+    op: Comparison,
+    current: Value,
+    recorded: Value,
+    operand: u64,
+) -> ProgramError {
+    msg!(&format!(
+        "Comparison failed: observed {current}, {op:?} {recorded} plus {operand}"
+    ));
+    RecordError::ComparisonFailed.into()
+}
+
+/// Stamps the header of a new record, with the `bump` of its address if it
+/// is program derived
+fn initialize_record(
+    data_info: &AccountInfo,
+    authority_info: &AccountInfo,
+    source_owner: &Option<Pubkey>,
+    token_mint: &Option<Pubkey>,
+    token_owner: &Option<Pubkey>,
+    bump: Option<u8>,
+) -> ProgramResult {
+    let raw_data = &mut borrow_mut_data(data_info)?;
+    if raw_data.len() < RecordData::WRITABLE_START_INDEX {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let slot_count = u8::try_from(RecordData::slot_count_for(raw_data.len()))
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+
+    let account_data = bytemuck::try_from_bytes_mut::<RecordData>(
+        &mut raw_data[..RecordData::WRITABLE_START_INDEX],
+    )
+    .map_err(|_| ProgramError::InvalidArgument)?;
+
+    // records of an older layout must not be taken over by initializing
+    // them again
+    if account_data.version != RecordData::UNINITIALIZED_VERSION {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    account_data.authority = *authority_info.key();
+    account_data.version = RecordData::CURRENT_VERSION;
+    account_data.slot_count = slot_count;
+    account_data.set_source_owner(source_owner.as_ref());
+    account_data.set_token_mint(token_mint.as_ref());
+    account_data.set_token_owner(token_owner.as_ref());
+    if let Some(bump) = bump {
+        account_data.set_bump(bump);
+    }
+
+    RecordEvent::Initialize {
+        record: *data_info.key(),
+        authority: account_data.authority,
+        slot_count,
+    }
+    .log();
+
+    Ok(())
+}
+
 pub fn process_instruction(
-    _program_id: &Pubkey,
+    program_id: &Pubkey,
     accounts: &[AccountInfo],
     input: &[u8],
 ) -> ProgramResult {
+    // catches a binary deployed at another address than it was built for
+    if program_id != &crate::ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
     let instruction = RecordInstruction::unpack(input)?;
 
     match instruction {
-        RecordInstruction::Initialize => {
+        RecordInstruction::Initialize {
+            source_owner,
+            token_mint,
+            token_owner,
+        } => {
+            let data_info = record_info(program_id, accounts)?;
+            let authority_info = get_account_info!(accounts, 1);
+
+            initialize_record(
+                data_info,
+                authority_info,
+                &source_owner,
+                &token_mint,
+                &token_owner,
+                None,
+            )
+        }
+
+        RecordInstruction::InitializePda {
+            slot_count,
+            seed,
+            bump,
+            source_owner,
+            token_mint,
+            token_owner,
+        } => {
             let data_info = get_account_info!(accounts, 0);
             let authority_info = get_account_info!(accounts, 1);
+            let payer_info = get_account_info!(accounts, 2);
+            let system_program_info = get_account_info!(accounts, 3);
 
-            let raw_data = &mut data_info.try_borrow_mut_data().unwrap();
-            if raw_data.len() < RecordData::WRITABLE_START_INDEX {
-                return Err(ProgramError::InvalidAccountData);
+            // the authority picks the record configuration, nobody may
+            // claim its address first
+            if !authority_info.is_signer() {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if system_program_info.key() != &system::SYSTEM_PROGRAM_ID {
+                return Err(ProgramError::IncorrectProgramId);
+            }
+            if data_info.owner() == program_id {
+                return Err(ProgramError::AccountAlreadyInitialized);
             }
 
-            let account_data = bytemuck::try_from_bytes_mut::<RecordData>(
-                &mut raw_data[..RecordData::WRITABLE_START_INDEX],
+            let bump_seed = [bump];
+            let address = create_program_address(
+                &[
+                    RecordData::SEED_PREFIX,
+                    authority_info.key(),
+                    &seed,
+                    &bump_seed,
+                ],
+                program_id,
             )
-            .map_err(|_| ProgramError::InvalidArgument)?;
-
-            if account_data.is_initialized() {
-                return Err(ProgramError::AccountAlreadyInitialized);
+            .map_err(|_| ProgramError::InvalidSeeds)?;
+            if data_info.key() != &address {
+                return Err(ProgramError::InvalidSeeds);
+            }
+            // only the canonical bump, the highest one that derives an
+            // address, so an authority and seed map to a single record. Half
+            // of the bumps derive an address, so few are tried here.
+            for higher in u16::from(bump) + 1..=u16::from(u8::MAX) {
+                let higher_seed = [higher as u8];
+                let derived = create_program_address(
+                    &[
+                        RecordData::SEED_PREFIX,
+                        authority_info.key(),
+                        &seed,
+                        &higher_seed,
+                    ],
+                    program_id,
+                );
+                if derived.is_ok() {
+                    return Err(ProgramError::InvalidSeeds);
+                }
             }
 
-            account_data.authority = *authority_info.key();
-            account_data.version = RecordData::CURRENT_VERSION;
+            let space = RecordData::account_len(slot_count);
+            let rent = Rent::get()?.minimum_balance(space);
+            let seeds = [
+                Seed::from(RecordData::SEED_PREFIX),
+                Seed::from(authority_info.key()),
+                Seed::from(seed.as_slice()),
+                Seed::from(&bump_seed),
+            ];
+            let signers = [Signer::from(&seeds)];
 
-            Ok(())
+            // anyone can send lamports to the address beforehand, which
+            // would make create_account fail
+            let lamports = data_info.lamports();
+            if lamports == 0 {
+                system::create_account(
+                    payer_info,
+                    data_info,
+                    rent,
+                    space as u64,
+                    program_id,
+                    &signers,
+                )?;
+            } else {
+                if lamports < rent {
+                    system::transfer(payer_info, data_info, rent - lamports, &[])?;
+                }
+                system::allocate(data_info, space as u64, &signers)?;
+                system::assign(data_info, program_id, &signers)?;
+            }
+
+            initialize_record(
+                data_info,
+                authority_info,
+                &source_owner,
+                &token_mint,
+                &token_owner,
+                Some(bump),
+            )
         }
 
-        RecordInstruction::WriteU64 { offset } => {
-            let data_info = get_account_info!(accounts, 0);
+        RecordInstruction::WriteU64 {
+            slot,
+            offset,
+            value_type,
+            encoding,
+            pin_source,
+        } => {
+            let data_info = record_info(program_id, accounts)?;
             let authority_info = get_account_info!(accounts, 1);
             let read_account_info = get_account_info!(accounts, 2);
-            let raw_data = &data_info.try_borrow_data().unwrap();
-            if raw_data.len() < RecordData::WRITABLE_START_INDEX {
-                return Err(ProgramError::InvalidAccountData);
+
+            let (account_data, _) = load_slot(data_info, authority_info, read_account_info, slot)?;
+
+            let value = read_source(read_account_info, offset, value_type, &encoding)?;
+
+            let record_slot = RecordSlot::new(
+                value_type,
+                value,
+                read_account_info.key(),
+                offset,
+                pin_source,
+            );
+            store_slot(data_info, &account_data, slot, &record_slot)
+        }
+
+        RecordInstruction::CheckAdd {
+            slot,
+            offset,
+            value_type,
+            encoding,
+            addition,
+        } => {
+            let (old_value, new_value) =
+                load_check_values(program_id, accounts, slot, offset, value_type, &encoding)?;
+
+            check_increase(value_type, new_value, old_value, addition)
+        }
+
+        RecordInstruction::CheckSub {
+            slot,
+            offset,
+            value_type,
+            encoding,
+            min_decrease,
+            max_decrease,
+        } => {
+            let (old_value, new_value) =
+                load_check_values(program_id, accounts, slot, offset, value_type, &encoding)?;
+
+            let decrease = old_value.decrease_to(&new_value);
+            let error = if decrease < min_decrease as u128 {
+                Some(RecordError::DecreaseBelowMinimum)
+            } else if decrease > max_decrease as u128 {
+                Some(RecordError::DecreaseAboveMaximum)
+            } else {
+                None
+            };
+            if let Some(error) = error {
+                msg!(&format!(
+                    "Decrease out of range: observed {decrease}, required {min_decrease} to {max_decrease}"
+                ));
+                return Err(error.into());
             }
 
-            let account_data = bytemuck::try_from_bytes::<RecordData>(
-                &raw_data[..RecordData::WRITABLE_START_INDEX],
-            )
-            .map_err(|_| ProgramError::InvalidArgument)?;
+            Ok(())
+        }
+
+        RecordInstruction::CheckCompare {
+            slot,
+            offset,
+            value_type,
+            encoding,
+            op,
+            operand,
+        } => {
+            let (old_value, new_value) =
+                load_check_values(program_id, accounts, slot, offset, value_type, &encoding)?;
 
-            if !account_data.is_initialized() {
-                return Err(ProgramError::UninitializedAccount);
+            if !op.evaluate(new_value, old_value, operand)? {
+                return Err(comparison_failed(op, new_value, old_value, operand));
             }
-            check_authority(authority_info, &account_data.authority)?;
 
-            let raw_data = read_account_info.try_borrow_data().unwrap();
-            let data = &raw_data[offset as usize..offset as usize + 8];
-            data_info.try_borrow_mut_data().unwrap()
-                [RecordData::WRITABLE_START_INDEX..(RecordData::WRITABLE_START_INDEX + 8)]
-                .copy_from_slice(data);
+            Ok(())
+        }
+
+        RecordInstruction::CheckAddBps {
+            slot,
+            offset,
+            value_type,
+            encoding,
+            bps,
+        } => {
+            let (old_value, new_value) =
+                load_check_values(program_id, accounts, slot, offset, value_type, &encoding)?;
+
+            let required = value_type
+                .checked_add_bps(old_value, bps)
+                .ok_or(RecordError::BpsOverflow)?;
+            if new_value < required {
+                return Err(insufficient_increase(new_value, required));
+            }
 
+            set_return_data(&new_value.increase_from(&old_value).to_le_bytes());
             Ok(())
         }
 
-        RecordInstruction::CheckAdd { offset, addition } => {
-            let data_info = get_account_info!(accounts, 0);
+        RecordInstruction::CheckCompareAccounts {
+            offset_a,
+            offset_b,
+            value_type,
+            encoding,
+            op,
+            operand,
+            owner_a,
+            owner_b,
+        } => {
+            let account_a_info = get_account_info!(accounts, 0);
+            let account_b_info = get_account_info!(accounts, 1);
+
+            // without a record header, the expected owners come with the
+            // instruction, so look-alike accounts can't fake either side
+            for (info, owner) in [(account_a_info, owner_a), (account_b_info, owner_b)] {
+                if owner.is_some_and(|owner| info.owner() != &owner) {
+                    return Err(RecordError::IncorrectSourceOwner.into());
+                }
+            }
+
+            let value_a = read_source(account_a_info, offset_a, value_type, &encoding)?;
+            let value_b = read_source(account_b_info, offset_b, value_type, &encoding)?;
+
+            if !op.evaluate(value_a, value_b, operand)? {
+                return Err(comparison_failed(op, value_a, value_b, operand));
+            }
+
+            Ok(())
+        }
+
+        RecordInstruction::WriteTokenAmount { slot } => {
+            let data_info = record_info(program_id, accounts)?;
             let authority_info = get_account_info!(accounts, 1);
             let read_account_info = get_account_info!(accounts, 2);
 
-            let raw_data = &data_info.try_borrow_data().unwrap();
-            if raw_data.len() < RecordData::WRITABLE_START_INDEX {
-                return Err(ProgramError::InvalidAccountData);
-            }
-            let account_data = bytemuck::try_from_bytes::<RecordData>(
-                &raw_data[..RecordData::WRITABLE_START_INDEX],
+            let (account_data, _) = load_slot(data_info, authority_info, read_account_info, slot)?;
+            let token_account = load_token_account(&account_data, read_account_info)?;
+
+            let record_slot = RecordSlot::new(
+                ValueType::U64,
+                Value::Unsigned(token_account.amount as u128),
+                read_account_info.key(),
+                token::AMOUNT_OFFSET as u64,
+                true,
+            );
+            store_slot(data_info, &account_data, slot, &record_slot)
+        }
+
+        RecordInstruction::CheckTokenAdd { slot, addition } => {
+            let data_info = record_info(program_id, accounts)?;
+            let authority_info = get_account_info!(accounts, 1);
+            let read_account_info = get_account_info!(accounts, 2);
+
+            let (account_data, record_slot) =
+                load_slot(data_info, authority_info, read_account_info, slot)?;
+            let token_account = load_token_account(&account_data, read_account_info)?;
+
+            check_u64_increase(
+                &record_slot,
+                read_account_info.key(),
+                token::AMOUNT_OFFSET as u64,
+                token_account.amount,
+                addition,
             )
-            .map_err(|_| ProgramError::InvalidArgument)?;
+        }
 
-            if !account_data.is_initialized() {
-                return Err(ProgramError::UninitializedAccount);
-            }
-            check_authority(authority_info, &account_data.authority)?;
+        RecordInstruction::WriteMintSupply { slot } => {
+            let data_info = record_info(program_id, accounts)?;
+            let authority_info = get_account_info!(accounts, 1);
+            let read_account_info = get_account_info!(accounts, 2);
 
-            let old_data = &data_info.try_borrow_data()?
-                [RecordData::WRITABLE_START_INDEX..(RecordData::WRITABLE_START_INDEX + 8)];
-            let old_value = u64::from_le_bytes(old_data.try_into().unwrap());
+            let (account_data, _) = load_slot(data_info, authority_info, read_account_info, slot)?;
+            let token_mint = load_token_mint(&account_data, read_account_info)?;
 
-            let new_data =
-                &read_account_info.try_borrow_data()?[(offset as usize)..(offset as usize) + 8];
-            let new_value = u64::from_le_bytes(new_data.try_into().unwrap());
+            let record_slot = RecordSlot::new(
+                ValueType::U64,
+                Value::Unsigned(token_mint.supply as u128),
+                read_account_info.key(),
+                token::SUPPLY_OFFSET as u64,
+                true,
+            );
+            store_slot(data_info, &account_data, slot, &record_slot)
+        }
 
-            if new_value >= old_value + addition {
-                return Ok(());
-            }
+        RecordInstruction::CheckMintSupplyAdd { slot, addition } => {
+            let data_info = record_info(program_id, accounts)?;
+            let authority_info = get_account_info!(accounts, 1);
+            let read_account_info = get_account_info!(accounts, 2);
+
+            let (account_data, record_slot) =
+                load_slot(data_info, authority_info, read_account_info, slot)?;
+            let token_mint = load_token_mint(&account_data, read_account_info)?;
 
-            Err(ProgramError::UninitializedAccount)
+            check_u64_increase(
+                &record_slot,
+                read_account_info.key(),
+                token::SUPPLY_OFFSET as u64,
+                token_mint.supply,
+                addition,
+            )
         }
 
-        RecordInstruction::SetAuthority => {
-            let data_info = get_account_info!(accounts, 0);
+        RecordInstruction::WriteLamports { slot } => {
+            let data_info = record_info(program_id, accounts)?;
             let authority_info = get_account_info!(accounts, 1);
-            let new_authority_info = get_account_info!(accounts, 2);
-            let raw_data = &mut data_info.try_borrow_mut_data()?;
-            if raw_data.len() < RecordData::WRITABLE_START_INDEX {
-                return Err(ProgramError::InvalidAccountData);
-            }
+            let read_account_info = get_account_info!(accounts, 2);
 
-            let account_data = bytemuck::try_from_bytes_mut::<RecordData>(
-                &mut raw_data[..RecordData::WRITABLE_START_INDEX],
+            let (account_data, _) = load_slot(data_info, authority_info, read_account_info, slot)?;
+
+            let record_slot = RecordSlot::new(
+                ValueType::U64,
+                Value::Unsigned(read_account_info.lamports() as u128),
+                read_account_info.key(),
+                RecordSlot::LAMPORTS_OFFSET,
+                true,
+            );
+            store_slot(data_info, &account_data, slot, &record_slot)
+        }
+
+        RecordInstruction::CheckLamportsAdd { slot, addition } => {
+            let data_info = record_info(program_id, accounts)?;
+            let authority_info = get_account_info!(accounts, 1);
+            let read_account_info = get_account_info!(accounts, 2);
+
+            let (_, record_slot) = load_slot(data_info, authority_info, read_account_info, slot)?;
+
+            check_u64_increase(
+                &record_slot,
+                read_account_info.key(),
+                RecordSlot::LAMPORTS_OFFSET,
+                read_account_info.lamports(),
+                addition,
             )
-            .map_err(|_| ProgramError::InvalidArgument)?;
+        }
 
-            if !account_data.is_initialized() {
-                return Err(ProgramError::UninitializedAccount);
-            }
+        RecordInstruction::ReadU64 { slot } => {
+            let data_info = record_info(program_id, accounts)?;
+
+            let (_, record_slot) = read_slot(data_info, slot)?;
+            let mut return_data = [0u8; 17];
+            return_data[0] = record_slot.value_type;
+            return_data[1..].copy_from_slice(&record_slot.value);
+            set_return_data(&return_data);
+
+            Ok(())
+        }
+
+        RecordInstruction::SetAuthority => {
+            let data_info = record_info(program_id, accounts)?;
+            let authority_info = get_account_info!(accounts, 1);
+            let new_authority_info = get_account_info!(accounts, 2);
+            let raw_data = &mut borrow_mut_data(data_info)?;
+            let authority = record_authority(raw_data)?;
 
-            check_authority(authority_info, &account_data.authority)?;
-            account_data.authority = *new_authority_info.key();
+            check_authority(authority_info, authority)?;
+            RecordEvent::SetAuthority {
+                record: *data_info.key(),
+                old_authority: *authority,
+                new_authority: *new_authority_info.key(),
+            }
+            .log();
+            *authority = *new_authority_info.key();
 
             Ok(())
         }
         RecordInstruction::CloseAccount => {
-            let data_info = get_account_info!(accounts, 0);
+            let data_info = record_info(program_id, accounts)?;
             let authority_info = get_account_info!(accounts, 1);
             let destination_info = get_account_info!(accounts, 2);
-            let raw_data = &mut data_info.try_borrow_mut_data()?;
-            if raw_data.len() < RecordData::WRITABLE_START_INDEX {
-                return Err(ProgramError::InvalidAccountData);
+            if data_info.key() == destination_info.key() {
+                return Err(ProgramError::InvalidArgument);
             }
 
-            let account_data = bytemuck::try_from_bytes_mut::<RecordData>(
-                &mut raw_data[..RecordData::WRITABLE_START_INDEX],
-            )
-            .map_err(|_| ProgramError::InvalidArgument)?;
+            {
+                let raw_data = &mut borrow_mut_data(data_info)?;
+                check_authority(authority_info, record_authority(raw_data)?)?;
 
-            if !account_data.is_initialized() {
-                return Err(ProgramError::UninitializedAccount);
+                // wipe the header and every slot, so nothing recorded
+                // survives if the account is funded again in the same
+                // transaction
+                raw_data.fill(0);
+                raw_data[0] = RecordData::CLOSED_VERSION;
             }
-            check_authority(authority_info, &account_data.authority)?;
+            data_info.realloc(RecordData::CLOSED_LEN, false)?;
 
-            let destination_starting_lamports = *destination_info.try_borrow_lamports()?;
-            let data_lamports = *data_info.try_borrow_lamports()?;
-            *destination_info.try_borrow_mut_lamports().unwrap() = destination_starting_lamports
+            let destination_starting_lamports = destination_info.lamports();
+            let data_lamports = data_info.lamports();
+            *borrow_mut_lamports(destination_info)? = destination_starting_lamports
                 .checked_add(data_lamports)
                 .ok_or(RecordError::Overflow)?;
-            *data_info.try_borrow_mut_lamports().unwrap() = 0_u64;
+            *borrow_mut_lamports(data_info)? = 0_u64;
+
+            RecordEvent::Close {
+                record: *data_info.key(),
+                destination: *destination_info.key(),
+                lamports: data_lamports,
+            }
+            .log();
 
             Ok(())
         }
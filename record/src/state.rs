@@ -1,6 +1,10 @@
 use bytemuck::{Pod, Zeroable};
+use core::ops::Range;
+use num_traits::FromPrimitive;
 use pinocchio::pubkey::Pubkey;
 
+use crate::value::{Value, ValueType};
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
 pub struct RecordData {
@@ -9,18 +13,238 @@ pub struct RecordData {
 
     /// The account allowed to update the data
     pub authority: Pubkey,
+
+    /// Number of slots following the header
+    pub slot_count: u8,
+
+    /// Header flags, see [`RecordData::CHECK_SOURCE_OWNER`]
+    pub flags: u8,
+
+    /// Program that must own every source account read into this record
+    pub source_owner: Pubkey,
+
+    /// Mint required for token accounts read into this record
+    pub token_mint: Pubkey,
+
+    /// Owner required for token accounts read into this record
+    pub token_owner: Pubkey,
+
+    /// Bump seed of a program derived record, see [`RecordData::PROGRAM_DERIVED`]
+    pub bump: u8,
+}
+
+/// A recorded value, stored after the [`RecordData`] header
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct RecordSlot {
+    /// Type of the recorded value, see [`ValueType`]
+    pub value_type: u8,
+
+    /// Recorded value, widened to 16 little-endian bytes
+    pub value: [u8; 16],
+
+    /// Slot flags, see [`RecordSlot::PINNED`]
+    pub flags: u8,
+
+    /// The account the value was read from
+    pub source: Pubkey,
+
+    /// Offset of the value in the source account, little-endian
+    pub offset: [u8; 8],
+}
+
+impl RecordSlot {
+    /// Checks must read from the same source account and offset as the write
+    pub const PINNED: u8 = 1;
+
+    /// Offset recorded for lamport balances, which are not part of the
+    /// account data, so a lamport snapshot can never be checked as data
+    pub const LAMPORTS_OFFSET: u64 = u64::MAX;
+}
+
+impl RecordSlot {
+    pub fn new(
+        value_type: ValueType,
+        value: Value,
+        source: &Pubkey,
+        offset: u64,
+        pinned: bool,
+    ) -> Self {
+        let mut slot = Self::zeroed();
+        slot.set(value_type, value);
+        slot.set_source(source, offset, pinned);
+        slot
+    }
+
+    pub fn value_type(&self) -> Option<ValueType> {
+        ValueType::from_u8(self.value_type)
+    }
+
+    pub fn value(&self) -> Option<Value> {
+        self.value_type()
+            .map(|value_type| value_type.decode(self.value))
+    }
+
+    pub fn set(&mut self, value_type: ValueType, value: Value) {
+        self.value_type = value_type as u8;
+        self.value = value.encode();
+    }
+
+    pub fn set_source(&mut self, source: &Pubkey, offset: u64, pinned: bool) {
+        self.source = *source;
+        self.offset = offset.to_le_bytes();
+        self.flags = if pinned { Self::PINNED } else { 0 };
+    }
+
+    pub fn is_pinned(&self) -> bool {
+        self.flags & Self::PINNED != 0
+    }
+
+    /// Whether a check may read from `source` at `offset`
+    pub fn accepts_source(&self, source: &Pubkey, offset: u64) -> bool {
+        !self.is_pinned() || (self.source == *source && u64::from_le_bytes(self.offset) == offset)
+    }
 }
 
 impl RecordData {
-    /// Version to fill in on new created accounts
-    pub const CURRENT_VERSION: u8 = 1;
+    /// Version to fill in on new created accounts, bumped on every layout
+    /// change. Version 1 is the single-value layout with a 33 byte header.
+    pub const CURRENT_VERSION: u8 = 2;
+
+    /// Version of accounts that were never initialized
+    pub const UNINITIALIZED_VERSION: u8 = 0;
+
+    /// Version of the single-value layout. Such records can still be handed
+    /// over and closed, so their rent can be recovered.
+    pub const LEGACY_VERSION: u8 = 1;
+
+    /// Size of the legacy header, the version and the authority, which sits
+    /// at the same offset as in the current header
+    pub const LEGACY_HEADER_LEN: usize = 33;
 
-    /// Start of writable account data, after version and authority
-    pub const WRITABLE_START_INDEX: usize = 33;
+    /// Version of closed records, which can never be initialized again
+    pub const CLOSED_VERSION: u8 = u8::MAX;
+
+    /// Size a record is shrunk to when it is closed, keeping only the version
+    pub const CLOSED_LEN: usize = 1;
+
+    /// Start of writable account data, after the header
+    pub const WRITABLE_START_INDEX: usize = core::mem::size_of::<Self>();
+
+    /// Source accounts must be owned by `source_owner`
+    pub const CHECK_SOURCE_OWNER: u8 = 1;
+
+    /// Token accounts must be of `token_mint`
+    pub const CHECK_TOKEN_MINT: u8 = 1 << 1;
+
+    /// Token accounts must be owned by `token_owner`
+    pub const CHECK_TOKEN_OWNER: u8 = 1 << 2;
+
+    /// The record address is derived from [`RecordData::SEED_PREFIX`], the
+    /// authority and a user seed, with `bump`
+    pub const PROGRAM_DERIVED: u8 = 1 << 3;
+
+    /// First seed of program derived record addresses
+    pub const SEED_PREFIX: &'static [u8] = b"record";
+
+    /// Maximum length of the user seed of a program derived record
+    pub const MAX_SEED_LEN: usize = 32;
+
+    /// Size of a single slot in the writable account data
+    pub const SLOT_SIZE: usize = core::mem::size_of::<RecordSlot>();
 }
 
 impl RecordData {
     pub fn is_initialized(&self) -> bool {
         self.version == Self::CURRENT_VERSION
     }
+
+    pub fn source_owner(&self) -> Option<&Pubkey> {
+        (self.flags & Self::CHECK_SOURCE_OWNER != 0).then_some(&self.source_owner)
+    }
+
+    pub fn set_source_owner(&mut self, source_owner: Option<&Pubkey>) {
+        set_optional(
+            &mut self.flags,
+            Self::CHECK_SOURCE_OWNER,
+            &mut self.source_owner,
+            source_owner,
+        );
+    }
+
+    pub fn token_mint(&self) -> Option<&Pubkey> {
+        (self.flags & Self::CHECK_TOKEN_MINT != 0).then_some(&self.token_mint)
+    }
+
+    pub fn set_token_mint(&mut self, token_mint: Option<&Pubkey>) {
+        set_optional(
+            &mut self.flags,
+            Self::CHECK_TOKEN_MINT,
+            &mut self.token_mint,
+            token_mint,
+        );
+    }
+
+    pub fn token_owner(&self) -> Option<&Pubkey> {
+        (self.flags & Self::CHECK_TOKEN_OWNER != 0).then_some(&self.token_owner)
+    }
+
+    pub fn set_token_owner(&mut self, token_owner: Option<&Pubkey>) {
+        set_optional(
+            &mut self.flags,
+            Self::CHECK_TOKEN_OWNER,
+            &mut self.token_owner,
+            token_owner,
+        );
+    }
+
+    pub fn bump(&self) -> Option<u8> {
+        (self.flags & Self::PROGRAM_DERIVED != 0).then_some(self.bump)
+    }
+
+    pub fn set_bump(&mut self, bump: u8) {
+        self.flags |= Self::PROGRAM_DERIVED;
+        self.bump = bump;
+    }
+
+    /// Size of an account holding `slot_count` slots
+    pub fn account_len(slot_count: u8) -> usize {
+        Self::WRITABLE_START_INDEX + slot_count as usize * Self::SLOT_SIZE
+    }
+
+    /// Whether a source account owned by `owner` may be read into this record
+    pub fn accepts_source_owner(&self, owner: &Pubkey) -> bool {
+        match self.source_owner() {
+            Some(expected) => expected == owner,
+            None => true,
+        }
+    }
+
+    /// Number of slots that fit into an account of `data_len` bytes
+    pub fn slot_count_for(data_len: usize) -> usize {
+        data_len.saturating_sub(Self::WRITABLE_START_INDEX) / Self::SLOT_SIZE
+    }
+
+    /// Byte range of `slot` within the account data, if the slot exists
+    pub fn slot_range(&self, slot: u8) -> Option<Range<usize>> {
+        if slot >= self.slot_count {
+            return None;
+        }
+        let start = Self::WRITABLE_START_INDEX + slot as usize * Self::SLOT_SIZE;
+        Some(start..start + Self::SLOT_SIZE)
+    }
+}
+
+/// Stores an optional pubkey field guarded by `flag`
+fn set_optional(flags: &mut u8, flag: u8, field: &mut Pubkey, value: Option<&Pubkey>) {
+    match value {
+        Some(value) => {
+            *flags |= flag;
+            *field = *value;
+        }
+        None => {
+            *flags &= !flag;
+            *field = Pubkey::default();
+        }
+    }
 }
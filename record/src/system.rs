@@ -0,0 +1,91 @@
+use pinocchio::{
+    account_info::AccountInfo,
+    instruction::{AccountMeta, Instruction, Signer},
+    program::invoke_signed,
+    pubkey::Pubkey,
+    ProgramResult,
+};
+
+/// System program id, `11111111111111111111111111111111`
+pub const SYSTEM_PROGRAM_ID: Pubkey = [0; 32];
+
+const CREATE_ACCOUNT: u32 = 0;
+const ASSIGN: u32 = 1;
+const TRANSFER: u32 = 2;
+const ALLOCATE: u32 = 8;
+
+/// Creates `to_info` with `space` bytes owned by `owner`, funded by `from_info`
+pub fn create_account(
+    from_info: &AccountInfo,
+    to_info: &AccountInfo,
+    lamports: u64,
+    space: u64,
+    owner: &Pubkey,
+    signers: &[Signer],
+) -> ProgramResult {
+    let mut data = [0u8; 52];
+    data[..4].copy_from_slice(&CREATE_ACCOUNT.to_le_bytes());
+    data[4..12].copy_from_slice(&lamports.to_le_bytes());
+    data[12..20].copy_from_slice(&space.to_le_bytes());
+    data[20..].copy_from_slice(owner);
+
+    let instruction = Instruction {
+        program_id: &SYSTEM_PROGRAM_ID,
+        data: &data,
+        accounts: &[
+            AccountMeta::writable_signer(from_info.key()),
+            AccountMeta::writable_signer(to_info.key()),
+        ],
+    };
+    invoke_signed(&instruction, &[from_info, to_info], signers)
+}
+
+/// Moves `lamports` from the system account `from_info` to `to_info`
+pub fn transfer(
+    from_info: &AccountInfo,
+    to_info: &AccountInfo,
+    lamports: u64,
+    signers: &[Signer],
+) -> ProgramResult {
+    let mut data = [0u8; 12];
+    data[..4].copy_from_slice(&TRANSFER.to_le_bytes());
+    data[4..].copy_from_slice(&lamports.to_le_bytes());
+
+    let instruction = Instruction {
+        program_id: &SYSTEM_PROGRAM_ID,
+        data: &data,
+        accounts: &[
+            AccountMeta::writable_signer(from_info.key()),
+            AccountMeta::writable(to_info.key()),
+        ],
+    };
+    invoke_signed(&instruction, &[from_info, to_info], signers)
+}
+
+/// Allocates `space` bytes of data for the system account `account_info`
+pub fn allocate(account_info: &AccountInfo, space: u64, signers: &[Signer]) -> ProgramResult {
+    let mut data = [0u8; 12];
+    data[..4].copy_from_slice(&ALLOCATE.to_le_bytes());
+    data[4..].copy_from_slice(&space.to_le_bytes());
+
+    let instruction = Instruction {
+        program_id: &SYSTEM_PROGRAM_ID,
+        data: &data,
+        accounts: &[AccountMeta::writable_signer(account_info.key())],
+    };
+    invoke_signed(&instruction, &[account_info], signers)
+}
+
+/// Assigns the system account `account_info` to `owner`
+pub fn assign(account_info: &AccountInfo, owner: &Pubkey, signers: &[Signer]) -> ProgramResult {
+    let mut data = [0u8; 36];
+    data[..4].copy_from_slice(&ASSIGN.to_le_bytes());
+    data[4..].copy_from_slice(owner);
+
+    let instruction = Instruction {
+        program_id: &SYSTEM_PROGRAM_ID,
+        data: &data,
+        accounts: &[AccountMeta::writable_signer(account_info.key())],
+    };
+    invoke_signed(&instruction, &[account_info], signers)
+}
@@ -0,0 +1,107 @@
+use pinocchio::pubkey::Pubkey;
+
+use crate::error::RecordError;
+
+/// SPL Token program id, `TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA`
+pub const TOKEN_PROGRAM_ID: Pubkey = [
+    6, 221, 246, 225, 215, 101, 161, 147, 217, 203, 225, 70, 206, 235, 121, 172, 28, 180, 133, 237,
+    95, 91, 55, 145, 58, 140, 245, 133, 126, 255, 0, 169,
+];
+
+/// SPL Token-2022 program id, `TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb`
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = [
+    6, 221, 246, 225, 238, 117, 143, 222, 24, 66, 93, 188, 228, 108, 205, 218, 182, 26, 252, 77,
+    131, 185, 13, 39, 254, 189, 249, 40, 216, 161, 139, 252,
+];
+
+/// Size of an SPL Token account
+pub const ACCOUNT_LEN: usize = 165;
+
+/// Offset of the amount in an SPL Token account
+pub const AMOUNT_OFFSET: usize = 64;
+
+/// Size of an SPL Token mint
+pub const MINT_LEN: usize = 82;
+
+/// Offset of the supply in an SPL Token mint
+pub const SUPPLY_OFFSET: usize = 36;
+
+const MINT_OFFSET: usize = 0;
+const OWNER_OFFSET: usize = 32;
+const STATE_OFFSET: usize = 108;
+const STATE_INITIALIZED: u8 = 1;
+const MINT_IS_INITIALIZED_OFFSET: usize = 45;
+
+/// Token-2022 accounts and mints with extensions store their type right
+/// after the base token account layout, followed by the extension TLV data
+const ACCOUNT_TYPE_OFFSET: usize = ACCOUNT_LEN;
+const ACCOUNT_TYPE_MINT: u8 = 1;
+const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+
+/// Size of a multisig account, which extended accounts are never padded to
+const MULTISIG_LEN: usize = 355;
+
+/// Whether `data` of an account owned by `owner_program` has the layout of a
+/// token account or mint of `base_len` bytes, with Token-2022 extensions
+/// identified by `account_type`
+fn has_layout(owner_program: &Pubkey, data: &[u8], base_len: usize, account_type: u8) -> bool {
+    if owner_program == &TOKEN_PROGRAM_ID {
+        return data.len() == base_len;
+    }
+    if owner_program == &TOKEN_2022_PROGRAM_ID {
+        return data.len() == base_len
+            || (data.len() > ACCOUNT_TYPE_OFFSET
+                && data.len() != MULTISIG_LEN
+                && data[ACCOUNT_TYPE_OFFSET] == account_type);
+    }
+    false
+}
+
+/// Fields of an SPL Token account used by the record program
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TokenAccount {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+}
+
+impl TokenAccount {
+    /// Parses the data of an account owned by `owner_program` as an
+    /// initialized SPL Token or Token-2022 account
+    pub fn unpack(owner_program: &Pubkey, data: &[u8]) -> Result<Self, RecordError> {
+        if !has_layout(owner_program, data, ACCOUNT_LEN, ACCOUNT_TYPE_ACCOUNT) {
+            return Err(RecordError::InvalidTokenAccount);
+        }
+        if data[STATE_OFFSET] != STATE_INITIALIZED {
+            return Err(RecordError::TokenAccountNotInitialized);
+        }
+
+        Ok(Self {
+            mint: data[MINT_OFFSET..MINT_OFFSET + 32].try_into().unwrap(),
+            owner: data[OWNER_OFFSET..OWNER_OFFSET + 32].try_into().unwrap(),
+            amount: u64::from_le_bytes(data[AMOUNT_OFFSET..AMOUNT_OFFSET + 8].try_into().unwrap()),
+        })
+    }
+}
+
+/// Fields of an SPL Token mint used by the record program
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TokenMint {
+    pub supply: u64,
+}
+
+impl TokenMint {
+    /// Parses the data of an account owned by `owner_program` as an
+    /// initialized SPL Token or Token-2022 mint
+    pub fn unpack(owner_program: &Pubkey, data: &[u8]) -> Result<Self, RecordError> {
+        if !has_layout(owner_program, data, MINT_LEN, ACCOUNT_TYPE_MINT)
+            || data[MINT_IS_INITIALIZED_OFFSET] != 1
+        {
+            return Err(RecordError::InvalidMint);
+        }
+
+        Ok(Self {
+            supply: u64::from_le_bytes(data[SUPPLY_OFFSET..SUPPLY_OFFSET + 8].try_into().unwrap()),
+        })
+    }
+}
@@ -0,0 +1,203 @@
+use num_derive::FromPrimitive;
+
+/// Integer type of a value read from a source account and kept in a record slot.
+///
+/// `U64` is encoded as zero, so a slot that was never written reads as a u64 zero.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, FromPrimitive, PartialEq)]
+pub enum ValueType {
+    U64,
+    U8,
+    U16,
+    U32,
+    U128,
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+}
+
+impl core::fmt::Display for ValueType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Self::U8 => "u8",
+            Self::U16 => "u16",
+            Self::U32 => "u32",
+            Self::U64 => "u64",
+            Self::U128 => "u128",
+            Self::I8 => "i8",
+            Self::I16 => "i16",
+            Self::I32 => "i32",
+            Self::I64 => "i64",
+            Self::I128 => "i128",
+        })
+    }
+}
+
+impl ValueType {
+    /// Size of the little-endian encoded value in bytes
+    pub fn size(&self) -> usize {
+        match self {
+            Self::U8 | Self::I8 => 1,
+            Self::U16 | Self::I16 => 2,
+            Self::U32 | Self::I32 => 4,
+            Self::U64 | Self::I64 => 8,
+            Self::U128 | Self::I128 => 16,
+        }
+    }
+
+    pub fn is_signed(&self) -> bool {
+        matches!(
+            self,
+            Self::I8 | Self::I16 | Self::I32 | Self::I64 | Self::I128
+        )
+    }
+
+    /// Reads a value of this type at `offset`, if it fits in `data`
+    pub fn read(&self, data: &[u8], offset: usize, encoding: &Encoding) -> Option<Value> {
+        let size = self.size();
+        let bytes = data.get(offset..offset.checked_add(size)?)?;
+        let mut word = [0u8; 16];
+        word[..size].copy_from_slice(bytes);
+        if encoding.big_endian {
+            word[..size].reverse();
+        }
+
+        let mut raw = u128::from_le_bytes(word);
+        if let Some(field) = encoding.bit_field {
+            raw = (raw & field.mask)
+                .checked_shr(field.shift as u32)
+                .unwrap_or(0);
+        }
+        // sign-extend negative values to the full 16 bytes
+        let bits = size * 8;
+        if self.is_signed() && bits < 128 && (raw >> (bits - 1)) & 1 == 1 {
+            raw |= u128::MAX << bits;
+        }
+        Some(self.decode(raw.to_le_bytes()))
+    }
+
+    /// Whether `value` is representable in this type
+    pub fn contains(&self, value: &Value) -> bool {
+        let bits = self.size() as u32 * 8;
+        match *value {
+            Value::Unsigned(value) => {
+                !self.is_signed() && value.checked_shr(bits).unwrap_or(0) == 0
+            }
+            Value::Signed(value) => {
+                let high = value >> (bits - 1);
+                self.is_signed() && (high == 0 || high == -1)
+            }
+        }
+    }
+
+    /// Adds `amount` to `value`, failing if the sum is outside of this type
+    pub fn checked_add(&self, value: Value, amount: u64) -> Option<Value> {
+        value.checked_add(amount).filter(|sum| self.contains(sum))
+    }
+
+    /// Raises `value` by `bps` basis points, failing if the result is outside
+    /// of this type
+    pub fn checked_add_bps(&self, value: Value, bps: u16) -> Option<Value> {
+        value
+            .checked_add_bps(bps)
+            .filter(|scaled| self.contains(scaled))
+    }
+
+    /// Decodes a value stored in its 16-byte widened form
+    pub fn decode(&self, bytes: [u8; 16]) -> Value {
+        if self.is_signed() {
+            Value::Signed(i128::from_le_bytes(bytes))
+        } else {
+            Value::Unsigned(u128::from_le_bytes(bytes))
+        }
+    }
+}
+
+/// Byte order and optional bit field of a value in the source account
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Encoding {
+    pub big_endian: bool,
+    pub bit_field: Option<BitField>,
+}
+
+/// Field packed inside a larger integer, extracted as `(raw & mask) >> shift`.
+///
+/// Masking applies to the raw bits of the integer before it is interpreted as
+/// a signed or unsigned value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BitField {
+    pub mask: u128,
+    pub shift: u8,
+}
+
+/// Basis points in a whole
+pub const BPS_DENOMINATOR: u32 = 10_000;
+
+/// Integer value widened to 128 bits, keeping its signedness.
+///
+/// Values are only ever compared with values of the same [`ValueType`].
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Value {
+    Unsigned(u128),
+    Signed(i128),
+}
+
+impl core::fmt::Display for Value {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Unsigned(value) => write!(f, "{value}"),
+            Self::Signed(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+impl Value {
+    /// Encodes the value in its 16-byte widened form
+    pub fn encode(&self) -> [u8; 16] {
+        match self {
+            Self::Unsigned(value) => value.to_le_bytes(),
+            Self::Signed(value) => value.to_le_bytes(),
+        }
+    }
+
+    pub fn checked_add(&self, amount: u64) -> Option<Self> {
+        Some(match self {
+            Self::Unsigned(value) => Self::Unsigned(value.checked_add(amount as u128)?),
+            Self::Signed(value) => Self::Signed(value.checked_add(amount as i128)?),
+        })
+    }
+
+    /// Raises the value by `bps` basis points of its magnitude, rounding the
+    /// increase down, so negative values move towards zero
+    pub fn checked_add_bps(&self, bps: u16) -> Option<Self> {
+        let increase = |magnitude: u128| {
+            magnitude
+                .checked_mul(bps as u128)
+                .map(|scaled| scaled / BPS_DENOMINATOR as u128)
+        };
+        Some(match self {
+            Self::Unsigned(value) => Self::Unsigned(value.checked_add(increase(*value)?)?),
+            Self::Signed(value) => {
+                Self::Signed(value.checked_add_unsigned(increase(value.unsigned_abs())?)?)
+            }
+        })
+    }
+
+    /// Amount by which this value is higher than `recorded`, zero if it is not higher
+    pub fn increase_from(&self, recorded: &Self) -> u128 {
+        self.decrease_to(recorded)
+    }
+
+    /// Amount by which `current` is lower than this value, zero if it is not lower
+    pub fn decrease_to(&self, current: &Self) -> u128 {
+        match (self, current) {
+            (Self::Unsigned(old), Self::Unsigned(new)) => old.saturating_sub(*new),
+            (Self::Signed(old), Self::Signed(new)) => {
+                old.saturating_sub(*new).max(0).unsigned_abs()
+            }
+            _ => 0,
+        }
+    }
+}
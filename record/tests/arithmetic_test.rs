@@ -0,0 +1,215 @@
+use proptest::prelude::*;
+use record::{
+    error::RecordError,
+    instruction::Comparison,
+    value::{Encoding, Value, ValueType, BPS_DENOMINATOR},
+};
+
+const VALUE_TYPES: [ValueType; 10] = [
+    ValueType::U8,
+    ValueType::U16,
+    ValueType::U32,
+    ValueType::U64,
+    ValueType::U128,
+    ValueType::I8,
+    ValueType::I16,
+    ValueType::I32,
+    ValueType::I64,
+    ValueType::I128,
+];
+
+/// Smallest and largest value of `value_type`
+fn bounds(value_type: ValueType) -> (i128, u128) {
+    let bits = value_type.size() as u32 * 8;
+    if value_type.is_signed() {
+        let max = i128::MAX >> (128 - bits);
+        (-max - 1, max as u128)
+    } else {
+        (0, u128::MAX >> (128 - bits))
+    }
+}
+
+fn value_type() -> impl Strategy<Value = ValueType> {
+    proptest::sample::select(VALUE_TYPES.to_vec())
+}
+
+/// Raw 128-bit pattern biased towards the edges of every integer width
+fn raw_bits() -> impl Strategy<Value = u128> {
+    let edges = (0..=128u32)
+        .step_by(8)
+        .flat_map(|bits| {
+            let max = u128::MAX.checked_shr(128 - bits).unwrap_or(0);
+            [max.saturating_sub(1), max, max.wrapping_add(1)]
+        })
+        .collect::<Vec<_>>();
+    prop_oneof![
+        proptest::sample::select(edges),
+        Just(u128::MAX),
+        Just(1u128 << 127),
+        any::<u128>(),
+    ]
+}
+
+/// Value of `value_type` built from the low bits of `raw`, sign-extended
+fn value_of(value_type: ValueType, raw: u128) -> Value {
+    let mut data = [0u8; 16];
+    data.copy_from_slice(&raw.to_le_bytes());
+    value_type.read(&data, 0, &Encoding::default()).unwrap()
+}
+
+fn addition() -> impl Strategy<Value = u64> {
+    prop_oneof![
+        Just(0),
+        Just(1),
+        Just(u64::MAX - 1),
+        Just(u64::MAX),
+        any::<u64>(),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn read_stays_in_bounds(value_type in value_type(), raw in raw_bits()) {
+        let value = value_of(value_type, raw);
+        prop_assert!(value_type.contains(&value));
+
+        let (min, max) = bounds(value_type);
+        match value {
+            Value::Unsigned(value) => prop_assert!(value <= max),
+            Value::Signed(value) => {
+                prop_assert!(value >= min);
+                prop_assert!(value as u128 <= max || value < 0);
+            }
+        }
+    }
+
+    #[test]
+    fn encode_decode_roundtrip(value_type in value_type(), raw in raw_bits()) {
+        let value = value_of(value_type, raw);
+        prop_assert_eq!(value_type.decode(value.encode()), value);
+    }
+
+    #[test]
+    fn big_endian_matches_little_endian(value_type in value_type(), raw in raw_bits()) {
+        let size = value_type.size();
+        let mut data = raw.to_le_bytes()[..size].to_vec();
+        let little = value_type.read(&data, 0, &Encoding::default()).unwrap();
+        data.reverse();
+        let big = value_type
+            .read(&data, 0, &Encoding { big_endian: true, bit_field: None })
+            .unwrap();
+        prop_assert_eq!(little, big);
+    }
+
+    #[test]
+    fn read_out_of_bounds_fails(value_type in value_type(), len in 0usize..32, offset in any::<usize>()) {
+        let data = vec![0xffu8; len];
+        let fits = offset
+            .checked_add(value_type.size())
+            .is_some_and(|end| end <= len);
+        prop_assert_eq!(
+            value_type.read(&data, offset, &Encoding::default()).is_some(),
+            fits
+        );
+    }
+
+    #[test]
+    fn checked_add_never_wraps(value_type in value_type(), raw in raw_bits(), amount in addition()) {
+        let value = value_of(value_type, raw);
+        let (min, max) = bounds(value_type);
+        match (value, value_type.checked_add(value, amount)) {
+            (Value::Unsigned(value), sum) => {
+                let expected = value
+                    .checked_add(amount as u128)
+                    .filter(|sum| *sum <= max)
+                    .map(Value::Unsigned);
+                prop_assert_eq!(sum, expected);
+            }
+            (Value::Signed(value), sum) => {
+                let expected = value
+                    .checked_add(amount as i128)
+                    .filter(|sum| *sum >= min && (*sum < 0 || *sum as u128 <= max))
+                    .map(Value::Signed);
+                prop_assert_eq!(sum, expected);
+            }
+        }
+        if let Some(sum) = value_type.checked_add(value, amount) {
+            prop_assert!(sum >= value);
+            prop_assert!(value_type.contains(&sum));
+        }
+    }
+
+    #[test]
+    fn compare_ge_matches_wide_arithmetic(
+        value_type in value_type(),
+        current in raw_bits(),
+        recorded in raw_bits(),
+        operand in addition(),
+    ) {
+        let current = value_of(value_type, current);
+        let recorded = value_of(value_type, recorded);
+        match recorded.checked_add(operand) {
+            Some(target) => prop_assert_eq!(
+                Comparison::Ge.evaluate(current, recorded, operand),
+                Ok(current >= target)
+            ),
+            None => prop_assert_eq!(
+                Comparison::Ge.evaluate(current, recorded, operand),
+                Err(RecordError::Overflow)
+            ),
+        }
+    }
+
+    #[test]
+    fn huge_addition_never_passes(recorded in any::<u64>(), current in any::<u64>()) {
+        // the case that wraps with unchecked u64 arithmetic
+        let sum = ValueType::U64.checked_add(Value::Unsigned(recorded as u128), u64::MAX);
+        if recorded == 0 {
+            prop_assert_eq!(sum, Some(Value::Unsigned(u64::MAX as u128)));
+        } else {
+            prop_assert_eq!(sum, None);
+        }
+        prop_assert_eq!(
+            Comparison::Ge.evaluate(
+                Value::Unsigned(current as u128),
+                Value::Unsigned(recorded as u128),
+                u64::MAX
+            ),
+            Ok(current as u128 >= recorded as u128 + u64::MAX as u128)
+        );
+    }
+
+    #[test]
+    fn increase_adds_back_up(value_type in value_type(), current in raw_bits(), recorded in raw_bits()) {
+        let current = value_of(value_type, current);
+        let recorded = value_of(value_type, recorded);
+        let increase = current.increase_from(&recorded);
+        if current <= recorded {
+            prop_assert_eq!(increase, 0);
+        }
+        // any increase between two values of up to 64 bits fits an addition
+        if value_type.size() <= 8 && current >= recorded {
+            let addition = u64::try_from(increase).unwrap();
+            prop_assert_eq!(value_type.checked_add(recorded, addition), Some(current));
+        }
+    }
+
+    #[test]
+    fn add_bps_rounds_towards_zero(raw in any::<u64>(), bps in any::<u16>()) {
+        let value = Value::Unsigned(raw as u128);
+        let scaled = value.checked_add_bps(bps).unwrap();
+        let expected = raw as u128 * (BPS_DENOMINATOR as u128 + bps as u128)
+            / BPS_DENOMINATOR as u128;
+        prop_assert_eq!(scaled, Value::Unsigned(expected));
+        prop_assert!(scaled >= value);
+    }
+
+    #[test]
+    fn add_bps_never_lowers_signed(raw in any::<i64>(), bps in any::<u16>()) {
+        let value = Value::Signed(raw as i128);
+        let scaled = value.checked_add_bps(bps).unwrap();
+        let increase = raw.unsigned_abs() as u128 * bps as u128 / BPS_DENOMINATOR as u128;
+        prop_assert_eq!(scaled.increase_from(&value), increase);
+        prop_assert!(scaled >= value);
+    }
+}
@@ -0,0 +1,458 @@
+use record::{
+    client::{self, RecordAccount, RecordAccountSlot, RecordConfig, SourceValue},
+    error::RecordError,
+    instruction::{Comparison, RecordInstruction},
+    pinocchio::program_error::ProgramError,
+    state::{RecordData, RecordSlot},
+    value::{BitField, Encoding, Value, ValueType},
+};
+use solana_instruction::{AccountMeta, Instruction};
+use solana_pubkey::Pubkey;
+
+fn unpack(program_id: &Pubkey, instruction: &Instruction) -> RecordInstruction {
+    assert_eq!(&instruction.program_id, program_id);
+    RecordInstruction::unpack(&instruction.data).unwrap()
+}
+
+#[test]
+fn source_instruction_accounts() {
+    let program_id = Pubkey::new_unique();
+    let record = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let source = Pubkey::new_unique();
+    let value = SourceValue {
+        offset: 8,
+        value_type: ValueType::I32,
+        encoding: Encoding {
+            big_endian: true,
+            bit_field: Some(BitField {
+                mask: 0xff00,
+                shift: 8,
+            }),
+        },
+    };
+
+    let write = client::write_u64(&program_id, &record, &authority, &source, 2, value, false);
+    assert_eq!(
+        write.accounts,
+        vec![
+            AccountMeta::new(record, false),
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new_readonly(source, false),
+        ]
+    );
+    assert_eq!(
+        unpack(&program_id, &write),
+        RecordInstruction::WriteU64 {
+            slot: 2,
+            offset: 8,
+            value_type: ValueType::I32,
+            encoding: value.encoding,
+            pin_source: false,
+        }
+    );
+
+    let check_accounts = vec![
+        AccountMeta::new_readonly(record, false),
+        AccountMeta::new_readonly(authority, true),
+        AccountMeta::new_readonly(source, false),
+    ];
+    let checks = [
+        (
+            client::check_add(&program_id, &record, &authority, &source, 2, value, 5),
+            RecordInstruction::CheckAdd {
+                slot: 2,
+                offset: 8,
+                value_type: ValueType::I32,
+                encoding: value.encoding,
+                addition: 5,
+            },
+        ),
+        (
+            client::check_sub(&program_id, &record, &authority, &source, 2, value, 1, 3),
+            RecordInstruction::CheckSub {
+                slot: 2,
+                offset: 8,
+                value_type: ValueType::I32,
+                encoding: value.encoding,
+                min_decrease: 1,
+                max_decrease: 3,
+            },
+        ),
+        (
+            client::check_compare(
+                &program_id,
+                &record,
+                &authority,
+                &source,
+                2,
+                value,
+                Comparison::Range { upper: 9 },
+                4,
+            ),
+            RecordInstruction::CheckCompare {
+                slot: 2,
+                offset: 8,
+                value_type: ValueType::I32,
+                encoding: value.encoding,
+                op: Comparison::Range { upper: 9 },
+                operand: 4,
+            },
+        ),
+        (
+            client::check_add_bps(&program_id, &record, &authority, &source, 2, value, 250),
+            RecordInstruction::CheckAddBps {
+                slot: 2,
+                offset: 8,
+                value_type: ValueType::I32,
+                encoding: value.encoding,
+                bps: 250,
+            },
+        ),
+        (
+            client::check_token_add(&program_id, &record, &authority, &source, 2, 7),
+            RecordInstruction::CheckTokenAdd {
+                slot: 2,
+                addition: 7,
+            },
+        ),
+        (
+            client::check_mint_supply_add(&program_id, &record, &authority, &source, 2, 7),
+            RecordInstruction::CheckMintSupplyAdd {
+                slot: 2,
+                addition: 7,
+            },
+        ),
+        (
+            client::check_lamports_add(&program_id, &record, &authority, &source, 2, 7),
+            RecordInstruction::CheckLamportsAdd {
+                slot: 2,
+                addition: 7,
+            },
+        ),
+    ];
+    for (instruction, expected) in checks {
+        assert_eq!(instruction.accounts, check_accounts);
+        assert_eq!(unpack(&program_id, &instruction), expected);
+    }
+
+    let writes = [
+        (
+            client::write_token_amount(&program_id, &record, &authority, &source, 1),
+            RecordInstruction::WriteTokenAmount { slot: 1 },
+        ),
+        (
+            client::write_mint_supply(&program_id, &record, &authority, &source, 1),
+            RecordInstruction::WriteMintSupply { slot: 1 },
+        ),
+        (
+            client::write_lamports(&program_id, &record, &authority, &source, 1),
+            RecordInstruction::WriteLamports { slot: 1 },
+        ),
+    ];
+    for (instruction, expected) in writes {
+        assert_eq!(instruction.accounts, write.accounts);
+        assert_eq!(unpack(&program_id, &instruction), expected);
+    }
+}
+
+#[test]
+fn record_instruction_accounts() {
+    let program_id = Pubkey::new_unique();
+    let record = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let other = Pubkey::new_unique();
+    let token_program = Pubkey::new_unique();
+
+    let initialize = client::initialize(&program_id, &record, &authority);
+    assert_eq!(
+        initialize.accounts,
+        vec![
+            AccountMeta::new(record, false),
+            AccountMeta::new_readonly(authority, false),
+        ]
+    );
+    assert_eq!(
+        unpack(&program_id, &initialize),
+        RecordInstruction::Initialize {
+            source_owner: None,
+            token_mint: None,
+            token_owner: None,
+        }
+    );
+
+    let config = RecordConfig {
+        source_owner: Some(other),
+        token_mint: None,
+        token_owner: Some(authority),
+    };
+    assert_eq!(
+        unpack(
+            &program_id,
+            &client::initialize_with_config(&program_id, &record, &authority, &config)
+        ),
+        RecordInstruction::Initialize {
+            source_owner: Some(other.to_bytes()),
+            token_mint: None,
+            token_owner: Some(authority.to_bytes()),
+        }
+    );
+
+    let set_authority = client::set_authority(&program_id, &record, &authority, &other);
+    assert_eq!(
+        set_authority.accounts,
+        vec![
+            AccountMeta::new(record, false),
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new_readonly(other, false),
+        ]
+    );
+    assert_eq!(
+        unpack(&program_id, &set_authority),
+        RecordInstruction::SetAuthority
+    );
+
+    let close = client::close_account(&program_id, &record, &authority, &other);
+    assert_eq!(
+        close.accounts,
+        vec![
+            AccountMeta::new(record, false),
+            AccountMeta::new_readonly(authority, true),
+            AccountMeta::new(other, false),
+        ]
+    );
+    assert_eq!(unpack(&program_id, &close), RecordInstruction::CloseAccount);
+
+    let read = client::read_u64(&program_id, &record, 4);
+    assert_eq!(
+        read.accounts,
+        vec![AccountMeta::new_readonly(record, false)]
+    );
+    assert_eq!(
+        unpack(&program_id, &read),
+        RecordInstruction::ReadU64 { slot: 4 }
+    );
+
+    let compare = client::check_compare_accounts(
+        &program_id,
+        &record,
+        &other,
+        SourceValue::u64(64),
+        72,
+        Comparison::Ge,
+        0,
+        Some(token_program),
+        None,
+    );
+    assert_eq!(
+        compare.accounts,
+        vec![
+            AccountMeta::new_readonly(record, false),
+            AccountMeta::new_readonly(other, false),
+        ]
+    );
+    assert_eq!(
+        unpack(&program_id, &compare),
+        RecordInstruction::CheckCompareAccounts {
+            offset_a: 64,
+            offset_b: 72,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+            op: Comparison::Ge,
+            operand: 0,
+            owner_a: Some(token_program.to_bytes()),
+            owner_b: None,
+        }
+    );
+}
+
+#[test]
+fn program_id_from_build_env() {
+    // the tests build with the `localnet` feature
+    let expected =
+        option_env!("RECORD_PROGRAM_ID").unwrap_or("RecordLoca1net11111111111111111111111111111");
+    assert_eq!(client::id().to_string(), expected);
+    assert_ne!(client::id(), solana_system_interface::program::ID);
+}
+
+#[test]
+fn record_account_creation() {
+    let program_id = Pubkey::new_unique();
+    let payer = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let record = Pubkey::new_unique();
+
+    assert_eq!(
+        client::account_len(3),
+        RecordData::WRITABLE_START_INDEX + 3 * RecordData::SLOT_SIZE
+    );
+    let create = client::create_account(&program_id, &payer, &record, 3, 1_000);
+    assert_eq!(
+        create.accounts,
+        vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(record, true)
+        ]
+    );
+
+    let (address, bump) = client::find_record_address(&program_id, &authority, b"vault");
+    assert_ne!(
+        client::find_record_address(&client::id(), &authority, b"vault").0,
+        address
+    );
+    let initialize = client::initialize_pda(
+        &program_id,
+        &authority,
+        &payer,
+        b"vault",
+        3,
+        &RecordConfig::default(),
+    )
+    .unwrap();
+    assert_eq!(initialize.accounts[0], AccountMeta::new(address, false));
+    assert_eq!(
+        initialize.accounts[1],
+        AccountMeta::new_readonly(authority, true)
+    );
+    assert_eq!(initialize.accounts[2], AccountMeta::new(payer, true));
+    assert_eq!(
+        unpack(&program_id, &initialize),
+        RecordInstruction::InitializePda {
+            slot_count: 3,
+            seed: b"vault".to_vec(),
+            bump,
+            source_owner: None,
+            token_mint: None,
+            token_owner: None,
+        }
+    );
+
+    let long_seed = [7; RecordData::MAX_SEED_LEN + 1];
+    assert_eq!(
+        client::initialize_pda(
+            &program_id,
+            &authority,
+            &payer,
+            &long_seed,
+            3,
+            &RecordConfig::default(),
+        ),
+        Err(ProgramError::MaxSeedLengthExceeded)
+    );
+}
+
+fn record_data(slot_count: u8) -> Vec<u8> {
+    let mut data = vec![0; RecordData::account_len(slot_count)];
+    let header =
+        bytemuck::from_bytes_mut::<RecordData>(&mut data[..RecordData::WRITABLE_START_INDEX]);
+    header.version = RecordData::CURRENT_VERSION;
+    header.authority = [1; 32];
+    header.slot_count = slot_count;
+    header.set_token_mint(Some(&[2; 32]));
+    header.set_bump(254);
+    data
+}
+
+#[test]
+fn decode_record_account() {
+    let mut data = record_data(3);
+    let header = *bytemuck::from_bytes::<RecordData>(&data[..RecordData::WRITABLE_START_INDEX]);
+    let slot = RecordSlot::new(ValueType::I32, Value::Signed(-5), &[3; 32], 8, true);
+    data[header.slot_range(0).unwrap()].copy_from_slice(bytemuck::bytes_of(&slot));
+    let slot = RecordSlot::new(
+        ValueType::U64,
+        Value::Unsigned(1_000),
+        &[4; 32],
+        RecordSlot::LAMPORTS_OFFSET,
+        false,
+    );
+    data[header.slot_range(2).unwrap()].copy_from_slice(bytemuck::bytes_of(&slot));
+
+    let account = RecordAccount::unpack(&data).unwrap();
+    assert_eq!(account.version, RecordData::CURRENT_VERSION);
+    assert_eq!(account.authority, Pubkey::new_from_array([1; 32]));
+    assert_eq!(account.source_owner, None);
+    assert_eq!(account.token_mint, Some(Pubkey::new_from_array([2; 32])));
+    assert_eq!(account.token_owner, None);
+    assert_eq!(account.bump, Some(254));
+    assert_eq!(
+        account.slots,
+        vec![
+            RecordAccountSlot {
+                value_type: ValueType::I32,
+                value: Value::Signed(-5),
+                source: Pubkey::new_from_array([3; 32]),
+                offset: 8,
+                pinned: true,
+            },
+            RecordAccountSlot {
+                value_type: ValueType::U64,
+                value: Value::Unsigned(0),
+                source: Pubkey::default(),
+                offset: 0,
+                pinned: false,
+            },
+            RecordAccountSlot {
+                value_type: ValueType::U64,
+                value: Value::Unsigned(1_000),
+                source: Pubkey::new_from_array([4; 32]),
+                offset: RecordSlot::LAMPORTS_OFFSET,
+                pinned: false,
+            },
+        ]
+    );
+
+    let display = account.to_string();
+    assert!(display.contains(&format!("Authority: {}", account.authority)));
+    assert!(display.contains("Bump: 254"));
+    assert!(!display.contains("Source owner"));
+    assert!(display.contains(&format!(
+        "[0] i32 -5 from {} at offset 8, pinned",
+        Pubkey::new_from_array([3; 32])
+    )));
+    assert!(display.contains(&format!(
+        "[2] u64 1000 lamports of {}",
+        Pubkey::new_from_array([4; 32])
+    )));
+}
+
+#[test]
+fn decode_record_account_fail() {
+    let data = record_data(2);
+
+    assert_eq!(
+        RecordAccount::unpack(&data[..data.len() - 1]),
+        Err(ProgramError::InvalidAccountData)
+    );
+    assert_eq!(
+        RecordAccount::unpack(&data[..RecordData::WRITABLE_START_INDEX - 1]),
+        Err(ProgramError::InvalidAccountData)
+    );
+
+    let mut uninitialized = data.clone();
+    uninitialized[0] = 0;
+    assert_eq!(
+        RecordAccount::unpack(&uninitialized),
+        Err(ProgramError::UninitializedAccount)
+    );
+
+    // version 1 records have the single-value layout
+    let mut legacy = data.clone();
+    legacy[0] = 1;
+    assert_eq!(
+        RecordAccount::unpack(&legacy),
+        Err(ProgramError::UninitializedAccount)
+    );
+
+    assert_eq!(
+        RecordAccount::unpack(&[RecordData::CLOSED_VERSION]),
+        Err(RecordError::RecordClosed.into())
+    );
+
+    let mut unknown_type = data;
+    unknown_type[RecordData::WRITABLE_START_INDEX] = 0xff;
+    assert_eq!(
+        RecordAccount::unpack(&unknown_type),
+        Err(ProgramError::InvalidAccountData)
+    );
+}
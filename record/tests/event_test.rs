@@ -0,0 +1,62 @@
+use record::{
+    event::{EventError, RecordEvent, EVENT_VERSION},
+    value::{Value, ValueType},
+};
+
+#[test]
+fn pack_unpack_roundtrip() {
+    let events = [
+        RecordEvent::Initialize {
+            record: [1; 32],
+            authority: [2; 32],
+            slot_count: 3,
+        },
+        RecordEvent::Write {
+            record: [1; 32],
+            slot: 2,
+            value_type: ValueType::I32,
+            value: Value::Signed(-5),
+            source: [3; 32],
+            offset: 64,
+            pinned: true,
+        },
+        RecordEvent::SetAuthority {
+            record: [1; 32],
+            old_authority: [2; 32],
+            new_authority: [4; 32],
+        },
+        RecordEvent::Close {
+            record: [1; 32],
+            destination: [5; 32],
+            lamports: 1_000_000,
+        },
+    ];
+    for event in events {
+        let packed = event.pack();
+        assert_eq!(packed[0], EVENT_VERSION);
+        assert_eq!(RecordEvent::unpack(&packed).unwrap(), event);
+        assert_eq!(
+            RecordEvent::unpack(&packed[..packed.len() - 1]),
+            Err(EventError::InvalidData)
+        );
+    }
+}
+
+#[test]
+fn unpack_unknown_event() {
+    let mut packed = RecordEvent::Close {
+        record: [1; 32],
+        destination: [5; 32],
+        lamports: 1,
+    }
+    .pack();
+    packed[1] = 0xff;
+    assert_eq!(RecordEvent::unpack(&packed), Err(EventError::InvalidData));
+
+    packed[0] = EVENT_VERSION + 1;
+    assert_eq!(
+        RecordEvent::unpack(&packed),
+        Err(EventError::UnknownVersion)
+    );
+    assert_eq!(RecordEvent::unpack(&[]), Err(EventError::InvalidData));
+}
@@ -1,34 +1,41 @@
 use solana_program::instruction::{AccountMeta, Instruction};
 use {
-    record::state::RecordData,
+    record::{
+        client::RecordAccount,
+        error::RecordError,
+        event::RecordEvent,
+        state::RecordData,
+        value::{BitField, Encoding, Value, ValueType},
+    },
     solana_program_test::*,
     solana_sdk::{
         account::Account,
+        instruction::InstructionError,
         pubkey::Pubkey,
         signature::{Keypair, Signer},
         system_instruction,
-        transaction::Transaction,
+        transaction::{Transaction, TransactionError},
     },
 };
 
-use record::instruction::RecordInstruction;
+use record::instruction::{Comparison, RecordInstruction};
 use solana_program::program_pack::Pack;
 use solana_program::rent::Rent;
 use solana_program_option::COption;
-use spl_token::state::Account as TokenAccount;
+use spl_token::state::{Account as TokenAccount, Mint};
 
-async fn initialize_storage_account(
+async fn create_storage_account(
     context: &mut ProgramTestContext,
     authority: &Keypair,
     account: &Keypair,
-    read_account: &Pubkey,
-    data: &[u8],
+    data_len: usize,
+    initialize: RecordInstruction,
 ) {
     let account_length = std::mem::size_of::<RecordData>()
-        .checked_add(data.len())
+        .checked_add(data_len)
         .unwrap();
     let custom_program_id = Pubkey::new_from_array(record::ID);
-    let data = RecordInstruction::Initialize.pack();
+    let data = initialize.pack();
     let ix = Instruction {
         program_id: custom_program_id,
         accounts: vec![
@@ -58,8 +65,31 @@ async fn initialize_storage_account(
         .process_transaction(transaction)
         .await
         .unwrap();
+}
+
+async fn initialize_storage_account(
+    context: &mut ProgramTestContext,
+    authority: &Keypair,
+    account: &Keypair,
+    read_account: &Pubkey,
+    data: &[u8],
+) {
+    let initialize = RecordInstruction::Initialize {
+        source_owner: None,
+        token_mint: None,
+        token_owner: None,
+    };
+    create_storage_account(context, authority, account, data.len(), initialize).await;
+    let custom_program_id = Pubkey::new_from_array(record::ID);
 
-    let data = RecordInstruction::WriteU64 { offset: 64 }.pack();
+    let data = RecordInstruction::WriteU64 {
+        slot: 0,
+        offset: 64,
+        value_type: ValueType::U64,
+        encoding: Encoding::default(),
+        pin_source: true,
+    }
+    .pack();
     let ix = Instruction {
         program_id: custom_program_id,
         accounts: vec![
@@ -117,7 +147,7 @@ async fn initialize_success() {
 
     let authority = Keypair::new();
     let account = Keypair::new();
-    let data = &[0u8; 8];
+    let data = &[0u8; RecordData::SLOT_SIZE];
     initialize_storage_account(
         &mut context,
         &authority,
@@ -134,13 +164,8 @@ async fn initialize_success() {
         .unwrap()
         .unwrap();
 
-    let record_value = u64::from_le_bytes(
-        record_account.data
-            [RecordData::WRITABLE_START_INDEX..(RecordData::WRITABLE_START_INDEX + 8)]
-            .try_into()
-            .unwrap(),
-    );
-    assert_eq!(record_value, 999);
+    let record_value = read_slot(&record_account.data, 0);
+    assert_eq!(record_value, Value::Unsigned(999));
 }
 
 #[tokio::test]
@@ -177,7 +202,7 @@ async fn check_add_success() {
 
     let authority = Keypair::new();
     let account = Keypair::new();
-    let data = &[0u8; 8];
+    let data = &[0u8; RecordData::SLOT_SIZE];
     initialize_storage_account(
         &mut context,
         &authority,
@@ -194,16 +219,14 @@ async fn check_add_success() {
         .unwrap()
         .unwrap();
 
-    let record_value = u64::from_le_bytes(
-        record_account.data
-            [RecordData::WRITABLE_START_INDEX..(RecordData::WRITABLE_START_INDEX + 8)]
-            .try_into()
-            .unwrap(),
-    );
-    assert_eq!(record_value, 999);
+    let record_value = read_slot(&record_account.data, 0);
+    assert_eq!(record_value, Value::Unsigned(999));
 
     let data = RecordInstruction::CheckAdd {
+        slot: 0,
         offset: 64,
+        value_type: ValueType::U64,
+        encoding: Encoding::default(),
         addition: 0,
     }
     .pack();
@@ -266,7 +289,7 @@ async fn check_add_fail() {
 
     let authority = Keypair::new();
     let account = Keypair::new();
-    let data = &[0u8; 8];
+    let data = &[0u8; RecordData::SLOT_SIZE];
     initialize_storage_account(
         &mut context,
         &authority,
@@ -283,16 +306,14 @@ async fn check_add_fail() {
         .unwrap()
         .unwrap();
 
-    let record_value = u64::from_le_bytes(
-        record_account.data
-            [RecordData::WRITABLE_START_INDEX..(RecordData::WRITABLE_START_INDEX + 8)]
-            .try_into()
-            .unwrap(),
-    );
-    assert_eq!(record_value, 999);
+    let record_value = read_slot(&record_account.data, 0);
+    assert_eq!(record_value, Value::Unsigned(999));
 
     let data = RecordInstruction::CheckAdd {
+        slot: 0,
         offset: 64,
+        value_type: ValueType::U64,
+        encoding: Encoding::default(),
         addition: 1,
     }
     .pack();
@@ -314,11 +335,24 @@ async fn check_add_fail() {
         &[&context.payer, &authority],
         context.last_blockhash,
     );
-    assert!(context
+    let result = context
         .banks_client
-        .process_transaction(transaction)
+        .process_transaction_with_metadata(transaction)
         .await
-        .is_err());
+        .unwrap();
+    assert_eq!(
+        result.result.unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RecordError::InsufficientIncrease as u32)
+        )
+    );
+    assert!(result
+        .metadata
+        .unwrap()
+        .log_messages
+        .iter()
+        .any(|log| log.ends_with("Insufficient increase: observed 999, required at least 1000")));
 }
 
 #[tokio::test]
@@ -357,7 +391,7 @@ async fn set_authority_success() {
     let account = Keypair::new();
     let new_authority = Keypair::new();
 
-    let data = &[0u8; 8];
+    let data = &[0u8; RecordData::SLOT_SIZE];
     initialize_storage_account(
         &mut context,
         &authority,
@@ -454,7 +488,7 @@ async fn close_account_success() {
     let authority = Keypair::new();
     let account = Keypair::new();
 
-    let data = &[0u8; 8];
+    let data = &[0u8; RecordData::SLOT_SIZE];
     initialize_storage_account(
         &mut context,
         &authority,
@@ -504,3 +538,2474 @@ async fn close_account_success() {
         .unwrap();
     assert!(record_account.is_none());
 }
+
+fn add_token_account(program_test: &mut ProgramTest, amount: u64, delegated_amount: u64) -> Pubkey {
+    add_token_account_with(
+        program_test,
+        &Pubkey::new_unique(),
+        &Pubkey::new_unique(),
+        amount,
+        delegated_amount,
+    )
+}
+
+fn add_token_account_with(
+    program_test: &mut ProgramTest,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+    delegated_amount: u64,
+) -> Pubkey {
+    let pubkey = Pubkey::new_unique();
+    let token_account = TokenAccount {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount,
+        close_authority: COption::None,
+        delegate: COption::Some(Pubkey::new_unique()),
+    };
+
+    let mut data = [0u8; TokenAccount::LEN];
+    TokenAccount::pack(token_account, &mut data).unwrap();
+    program_test.add_account(
+        pubkey,
+        Account {
+            lamports: 100,
+            data: data.to_vec(),
+            owner: spl_token::id(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    pubkey
+}
+
+/// Adds a Token-2022 account carrying a transfer fee amount extension
+fn add_token_2022_account(
+    program_test: &mut ProgramTest,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) -> Pubkey {
+    let pubkey = Pubkey::new_unique();
+    let token_account = TokenAccount {
+        mint: *mint,
+        owner: *owner,
+        amount,
+        state: spl_token::state::AccountState::Initialized,
+        is_native: COption::None,
+        delegated_amount: 0,
+        close_authority: COption::None,
+        delegate: COption::None,
+    };
+
+    let mut data = vec![0u8; TokenAccount::LEN];
+    TokenAccount::pack(token_account, &mut data).unwrap();
+    // account type, then the extension type, length and withheld amount
+    data.push(2);
+    data.extend_from_slice(&2u16.to_le_bytes());
+    data.extend_from_slice(&8u16.to_le_bytes());
+    data.extend_from_slice(&0u64.to_le_bytes());
+    program_test.add_account(
+        pubkey,
+        Account {
+            lamports: 100,
+            data,
+            owner: Pubkey::new_from_array(record::token::TOKEN_2022_PROGRAM_ID),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    pubkey
+}
+
+fn add_mint(program_test: &mut ProgramTest, owner_program: &Pubkey, supply: u64) -> Pubkey {
+    let pubkey = Pubkey::new_unique();
+    let mint = Mint {
+        mint_authority: COption::Some(Pubkey::new_unique()),
+        supply,
+        decimals: 6,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    };
+
+    let mut data = [0u8; Mint::LEN];
+    Mint::pack(mint, &mut data).unwrap();
+    program_test.add_account(
+        pubkey,
+        Account {
+            lamports: 100,
+            data: data.to_vec(),
+            owner: *owner_program,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    pubkey
+}
+
+async fn set_mint_supply(context: &mut ProgramTestContext, pubkey: &Pubkey, supply: u64) {
+    let mut account = context
+        .banks_client
+        .get_account(*pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut mint = Mint::unpack(&account.data).unwrap();
+    mint.supply = supply;
+    Mint::pack(mint, &mut account.data).unwrap();
+    context.set_account(pubkey, &account.into());
+}
+
+async fn set_token_amount(context: &mut ProgramTestContext, pubkey: &Pubkey, amount: u64) {
+    let mut account = context
+        .banks_client
+        .get_account(*pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let mut token_account = TokenAccount::unpack(&account.data).unwrap();
+    token_account.amount = amount;
+    TokenAccount::pack(token_account, &mut account.data).unwrap();
+    context.set_account(pubkey, &account.into());
+}
+
+async fn process_authority_instruction(
+    context: &mut ProgramTestContext,
+    authority: &Keypair,
+    accounts: Vec<AccountMeta>,
+    data: Vec<u8>,
+) -> Result<(), BanksClientError> {
+    let ix = Instruction {
+        program_id: Pubkey::new_from_array(record::ID),
+        accounts,
+        data,
+    };
+    let transaction = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, authority],
+        context.last_blockhash,
+    );
+    context.banks_client.process_transaction(transaction).await
+}
+
+fn read_slot(data: &[u8], slot: u8) -> Value {
+    RecordAccount::unpack(data).unwrap().slots[slot as usize].value
+}
+
+#[tokio::test]
+async fn write_multiple_slots_success() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let mut program_test = ProgramTest::new("record", custom_program_id, None);
+    // delegated_amount lives at offset 121 of a token account with a delegate
+    let read_account_pubkey = add_token_account(&mut program_test, 999, 5);
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[0u8; 2 * RecordData::SLOT_SIZE];
+    initialize_storage_account(
+        &mut context,
+        &authority,
+        &account,
+        &read_account_pubkey,
+        data,
+    )
+    .await;
+
+    let accounts = vec![
+        AccountMeta::new(account.pubkey(), false),
+        AccountMeta::new_readonly(authority.pubkey(), true),
+        AccountMeta::new_readonly(read_account_pubkey, false),
+    ];
+    process_authority_instruction(
+        &mut context,
+        &authority,
+        accounts.clone(),
+        RecordInstruction::WriteU64 {
+            slot: 1,
+            offset: 121,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+            pin_source: true,
+        }
+        .pack(),
+    )
+    .await
+    .unwrap();
+
+    let record_account = context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    let header = bytemuck::try_from_bytes::<RecordData>(
+        &record_account.data[..RecordData::WRITABLE_START_INDEX],
+    )
+    .unwrap();
+    assert_eq!(header.slot_count, 2);
+    assert_eq!(read_slot(&record_account.data, 0), Value::Unsigned(999));
+    assert_eq!(read_slot(&record_account.data, 1), Value::Unsigned(5));
+
+    process_authority_instruction(
+        &mut context,
+        &authority,
+        accounts,
+        RecordInstruction::CheckAdd {
+            slot: 1,
+            offset: 121,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+            addition: 0,
+        }
+        .pack(),
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn write_invalid_slot_fail() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let mut program_test = ProgramTest::new("record", custom_program_id, None);
+    let read_account_pubkey = add_token_account(&mut program_test, 999, 0);
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[0u8; RecordData::SLOT_SIZE];
+    initialize_storage_account(
+        &mut context,
+        &authority,
+        &account,
+        &read_account_pubkey,
+        data,
+    )
+    .await;
+
+    let accounts = vec![
+        AccountMeta::new(account.pubkey(), false),
+        AccountMeta::new_readonly(authority.pubkey(), true),
+        AccountMeta::new_readonly(read_account_pubkey, false),
+    ];
+    let result = process_authority_instruction(
+        &mut context,
+        &authority,
+        accounts,
+        RecordInstruction::WriteU64 {
+            slot: 1,
+            offset: 64,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+            pin_source: true,
+        }
+        .pack(),
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RecordError::InvalidSlot as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn check_sub_success() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let mut program_test = ProgramTest::new("record", custom_program_id, None);
+    let read_account_pubkey = add_token_account(&mut program_test, 999, 0);
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[0u8; RecordData::SLOT_SIZE];
+    initialize_storage_account(
+        &mut context,
+        &authority,
+        &account,
+        &read_account_pubkey,
+        data,
+    )
+    .await;
+    set_token_amount(&mut context, &read_account_pubkey, 900).await;
+
+    let accounts = vec![
+        AccountMeta::new(account.pubkey(), false),
+        AccountMeta::new_readonly(authority.pubkey(), true),
+        AccountMeta::new_readonly(read_account_pubkey, false),
+    ];
+    process_authority_instruction(
+        &mut context,
+        &authority,
+        accounts,
+        RecordInstruction::CheckSub {
+            slot: 0,
+            offset: 64,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+            min_decrease: 50,
+            max_decrease: 99,
+        }
+        .pack(),
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn check_sub_fail() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let mut program_test = ProgramTest::new("record", custom_program_id, None);
+    let read_account_pubkey = add_token_account(&mut program_test, 999, 0);
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[0u8; RecordData::SLOT_SIZE];
+    initialize_storage_account(
+        &mut context,
+        &authority,
+        &account,
+        &read_account_pubkey,
+        data,
+    )
+    .await;
+    set_token_amount(&mut context, &read_account_pubkey, 900).await;
+
+    let accounts = vec![
+        AccountMeta::new(account.pubkey(), false),
+        AccountMeta::new_readonly(authority.pubkey(), true),
+        AccountMeta::new_readonly(read_account_pubkey, false),
+    ];
+    for (min_decrease, max_decrease, error) in [
+        (100, u64::MAX, RecordError::DecreaseBelowMinimum),
+        (0, 98, RecordError::DecreaseAboveMaximum),
+    ] {
+        let result = process_authority_instruction(
+            &mut context,
+            &authority,
+            accounts.clone(),
+            RecordInstruction::CheckSub {
+                slot: 0,
+                offset: 64,
+                value_type: ValueType::U64,
+                encoding: Encoding::default(),
+                min_decrease,
+                max_decrease,
+            }
+            .pack(),
+        )
+        .await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+        );
+    }
+}
+
+#[tokio::test]
+async fn check_compare_success() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let mut program_test = ProgramTest::new("record", custom_program_id, None);
+    let read_account_pubkey = add_token_account(&mut program_test, 999, 0);
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[0u8; RecordData::SLOT_SIZE];
+    initialize_storage_account(
+        &mut context,
+        &authority,
+        &account,
+        &read_account_pubkey,
+        data,
+    )
+    .await;
+
+    let accounts = vec![
+        AccountMeta::new(account.pubkey(), false),
+        AccountMeta::new_readonly(authority.pubkey(), true),
+        AccountMeta::new_readonly(read_account_pubkey, false),
+    ];
+    for (amount, op, operand) in [
+        (999, Comparison::Eq, 0),
+        (1_050, Comparison::Gt, 50),
+        (1_050, Comparison::Range { upper: 100 }, 51),
+    ] {
+        set_token_amount(&mut context, &read_account_pubkey, amount).await;
+        let accounts = accounts.clone();
+        process_authority_instruction(
+            &mut context,
+            &authority,
+            accounts,
+            RecordInstruction::CheckCompare {
+                slot: 0,
+                offset: 64,
+                value_type: ValueType::U64,
+                encoding: Encoding::default(),
+                op,
+                operand,
+            }
+            .pack(),
+        )
+        .await
+        .unwrap();
+    }
+}
+
+#[tokio::test]
+async fn check_compare_fail() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let mut program_test = ProgramTest::new("record", custom_program_id, None);
+    let read_account_pubkey = add_token_account(&mut program_test, 999, 0);
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[0u8; RecordData::SLOT_SIZE];
+    initialize_storage_account(
+        &mut context,
+        &authority,
+        &account,
+        &read_account_pubkey,
+        data,
+    )
+    .await;
+
+    let accounts = vec![
+        AccountMeta::new(account.pubkey(), false),
+        AccountMeta::new_readonly(authority.pubkey(), true),
+        AccountMeta::new_readonly(read_account_pubkey, false),
+    ];
+    let result = process_authority_instruction(
+        &mut context,
+        &authority,
+        accounts,
+        RecordInstruction::CheckCompare {
+            slot: 0,
+            offset: 64,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+            op: Comparison::Ne,
+            operand: 0,
+        }
+        .pack(),
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RecordError::ComparisonFailed as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn write_value_types_success() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let mut program_test = ProgramTest::new("record", custom_program_id, None);
+
+    let mut source_data = vec![0u8; 80];
+    source_data[0..8].copy_from_slice(&(-5i64).to_le_bytes());
+    source_data[8..24].copy_from_slice(&(u128::MAX - 1).to_le_bytes());
+    source_data[24] = 7;
+    source_data[64..72].copy_from_slice(&999u64.to_le_bytes());
+    let source_pubkey = Pubkey::new_unique();
+    program_test.add_account(
+        source_pubkey,
+        Account {
+            lamports: 100,
+            data: source_data,
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[0u8; 3 * RecordData::SLOT_SIZE];
+    initialize_storage_account(&mut context, &authority, &account, &source_pubkey, data).await;
+
+    let accounts = vec![
+        AccountMeta::new(account.pubkey(), false),
+        AccountMeta::new_readonly(authority.pubkey(), true),
+        AccountMeta::new_readonly(source_pubkey, false),
+    ];
+    for (slot, offset, value_type) in [
+        (0, 0, ValueType::I64),
+        (1, 8, ValueType::U128),
+        (2, 24, ValueType::U8),
+    ] {
+        process_authority_instruction(
+            &mut context,
+            &authority,
+            accounts.clone(),
+            RecordInstruction::WriteU64 {
+                slot,
+                offset,
+                value_type,
+                encoding: Encoding::default(),
+                pin_source: true,
+            }
+            .pack(),
+        )
+        .await
+        .unwrap();
+    }
+
+    let record_account = context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(read_slot(&record_account.data, 0), Value::Signed(-5));
+    assert_eq!(
+        read_slot(&record_account.data, 1),
+        Value::Unsigned(u128::MAX - 1)
+    );
+    assert_eq!(read_slot(&record_account.data, 2), Value::Unsigned(7));
+
+    process_authority_instruction(
+        &mut context,
+        &authority,
+        accounts.clone(),
+        RecordInstruction::CheckCompare {
+            slot: 0,
+            offset: 0,
+            value_type: ValueType::I64,
+            encoding: Encoding::default(),
+            op: Comparison::Eq,
+            operand: 0,
+        }
+        .pack(),
+    )
+    .await
+    .unwrap();
+
+    let result = process_authority_instruction(
+        &mut context,
+        &authority,
+        accounts,
+        RecordInstruction::CheckAdd {
+            slot: 1,
+            offset: 8,
+            value_type: ValueType::U128,
+            encoding: Encoding::default(),
+            addition: 2,
+        }
+        .pack(),
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RecordError::Overflow as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn check_value_type_mismatch_fail() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let mut program_test = ProgramTest::new("record", custom_program_id, None);
+    let read_account_pubkey = add_token_account(&mut program_test, 999, 0);
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[0u8; RecordData::SLOT_SIZE];
+    initialize_storage_account(
+        &mut context,
+        &authority,
+        &account,
+        &read_account_pubkey,
+        data,
+    )
+    .await;
+
+    let accounts = vec![
+        AccountMeta::new(account.pubkey(), false),
+        AccountMeta::new_readonly(authority.pubkey(), true),
+        AccountMeta::new_readonly(read_account_pubkey, false),
+    ];
+    let result = process_authority_instruction(
+        &mut context,
+        &authority,
+        accounts,
+        RecordInstruction::CheckAdd {
+            slot: 0,
+            offset: 64,
+            value_type: ValueType::I64,
+            encoding: Encoding::default(),
+            addition: 0,
+        }
+        .pack(),
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RecordError::ValueTypeMismatch as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn write_encoded_value_success() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let mut program_test = ProgramTest::new("record", custom_program_id, None);
+
+    let mut source_data = vec![0u8; 72];
+    source_data[0..8].copy_from_slice(&1_000u64.to_be_bytes());
+    // u16 packed into bits 16..32 of a little-endian u64
+    source_data[8..16].copy_from_slice(&(0xbeef_u64 << 16 | 0xffff).to_le_bytes());
+    source_data[64..72].copy_from_slice(&999u64.to_le_bytes());
+    let source_pubkey = Pubkey::new_unique();
+    program_test.add_account(
+        source_pubkey,
+        Account {
+            lamports: 100,
+            data: source_data,
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[0u8; 2 * RecordData::SLOT_SIZE];
+    initialize_storage_account(&mut context, &authority, &account, &source_pubkey, data).await;
+
+    let accounts = vec![
+        AccountMeta::new(account.pubkey(), false),
+        AccountMeta::new_readonly(authority.pubkey(), true),
+        AccountMeta::new_readonly(source_pubkey, false),
+    ];
+    let big_endian = Encoding {
+        big_endian: true,
+        bit_field: None,
+    };
+    let packed_u16 = Encoding {
+        big_endian: false,
+        bit_field: Some(BitField {
+            mask: 0xffff_0000,
+            shift: 16,
+        }),
+    };
+    for (slot, offset, encoding) in [(0, 0, big_endian), (1, 8, packed_u16)] {
+        process_authority_instruction(
+            &mut context,
+            &authority,
+            accounts.clone(),
+            RecordInstruction::WriteU64 {
+                slot,
+                offset,
+                value_type: ValueType::U64,
+                encoding,
+                pin_source: true,
+            }
+            .pack(),
+        )
+        .await
+        .unwrap();
+    }
+
+    let record_account = context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(read_slot(&record_account.data, 0), Value::Unsigned(1_000));
+    assert_eq!(read_slot(&record_account.data, 1), Value::Unsigned(0xbeef));
+
+    process_authority_instruction(
+        &mut context,
+        &authority,
+        accounts,
+        RecordInstruction::CheckCompare {
+            slot: 1,
+            offset: 8,
+            value_type: ValueType::U64,
+            encoding: packed_u16,
+            op: Comparison::Eq,
+            operand: 0,
+        }
+        .pack(),
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn check_add_bps() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let mut program_test = ProgramTest::new("record", custom_program_id, None);
+    let read_account_pubkey = add_token_account(&mut program_test, 1_000, 0);
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[0u8; RecordData::SLOT_SIZE];
+    initialize_storage_account(
+        &mut context,
+        &authority,
+        &account,
+        &read_account_pubkey,
+        data,
+    )
+    .await;
+    set_token_amount(&mut context, &read_account_pubkey, 1_003).await;
+
+    let accounts = vec![
+        AccountMeta::new(account.pubkey(), false),
+        AccountMeta::new_readonly(authority.pubkey(), true),
+        AccountMeta::new_readonly(read_account_pubkey, false),
+    ];
+    let check_bps = |bps| {
+        RecordInstruction::CheckAddBps {
+            slot: 0,
+            offset: 64,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+            bps,
+        }
+        .pack()
+    };
+    process_authority_instruction(&mut context, &authority, accounts.clone(), check_bps(30))
+        .await
+        .unwrap();
+
+    let result =
+        process_authority_instruction(&mut context, &authority, accounts.clone(), check_bps(40))
+            .await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RecordError::InsufficientIncrease as u32)
+        )
+    );
+
+    // any growth of u64::MAX is outside of the value type
+    set_token_amount(&mut context, &read_account_pubkey, u64::MAX).await;
+    process_authority_instruction(
+        &mut context,
+        &authority,
+        accounts.clone(),
+        RecordInstruction::WriteU64 {
+            slot: 0,
+            offset: 64,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+            pin_source: true,
+        }
+        .pack(),
+    )
+    .await
+    .unwrap();
+    let result =
+        process_authority_instruction(&mut context, &authority, accounts, check_bps(1)).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RecordError::BpsOverflow as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn check_add_bps_negative() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let mut program_test = ProgramTest::new("record", custom_program_id, None);
+    let read_account_pubkey = add_token_account(&mut program_test, 1_000, 0);
+    let source_pubkey = Pubkey::new_unique();
+    program_test.add_account(
+        source_pubkey,
+        Account {
+            lamports: 100,
+            data: (-1_000i64).to_le_bytes().to_vec(),
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[0u8; 2 * RecordData::SLOT_SIZE];
+    initialize_storage_account(
+        &mut context,
+        &authority,
+        &account,
+        &read_account_pubkey,
+        data,
+    )
+    .await;
+
+    let accounts = vec![
+        AccountMeta::new(account.pubkey(), false),
+        AccountMeta::new_readonly(authority.pubkey(), true),
+        AccountMeta::new_readonly(source_pubkey, false),
+    ];
+    process_authority_instruction(
+        &mut context,
+        &authority,
+        accounts.clone(),
+        RecordInstruction::WriteU64 {
+            slot: 1,
+            offset: 0,
+            value_type: ValueType::I64,
+            encoding: Encoding::default(),
+            pin_source: true,
+        }
+        .pack(),
+    )
+    .await
+    .unwrap();
+
+    let check_bps = RecordInstruction::CheckAddBps {
+        slot: 1,
+        offset: 0,
+        value_type: ValueType::I64,
+        encoding: Encoding::default(),
+        bps: 30,
+    }
+    .pack();
+    // 0.3% growth of -1000 is -997, a drop to -1002 must not pass
+    for (current, error) in [
+        (-1_002i64, Some(RecordError::InsufficientIncrease)),
+        (-998, Some(RecordError::InsufficientIncrease)),
+        (-997, None),
+    ] {
+        context.set_account(
+            &source_pubkey,
+            &Account {
+                lamports: 100,
+                data: current.to_le_bytes().to_vec(),
+                owner: Pubkey::new_unique(),
+                executable: false,
+                rent_epoch: 0,
+            }
+            .into(),
+        );
+        // the same check is sent again, it needs a new blockhash
+        context.last_blockhash = context
+            .banks_client
+            .get_new_latest_blockhash(&context.last_blockhash)
+            .await
+            .unwrap();
+        let result = process_authority_instruction(
+            &mut context,
+            &authority,
+            accounts.clone(),
+            check_bps.clone(),
+        )
+        .await;
+        match error {
+            Some(error) => assert_eq!(
+                result.unwrap_err().unwrap(),
+                TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+            ),
+            None => result.unwrap(),
+        }
+    }
+}
+
+#[tokio::test]
+async fn check_compare_accounts() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let mut program_test = ProgramTest::new("record", custom_program_id, None);
+    let vault_pubkey = add_token_account(&mut program_test, 1_000, 0);
+
+    let mut liability_data = vec![0u8; 16];
+    liability_data[8..16].copy_from_slice(&900u64.to_le_bytes());
+    let liability_pubkey = Pubkey::new_unique();
+    let liability_owner = Pubkey::new_unique();
+    program_test.add_account(
+        liability_pubkey,
+        Account {
+            lamports: 100,
+            data: liability_data,
+            owner: liability_owner,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let accounts = vec![
+        AccountMeta::new_readonly(vault_pubkey, false),
+        AccountMeta::new_readonly(liability_pubkey, false),
+    ];
+    let check_solvency = |operand, owner_b: Pubkey| {
+        RecordInstruction::CheckCompareAccounts {
+            offset_a: 64,
+            offset_b: 8,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+            op: Comparison::Ge,
+            operand,
+            owner_a: Some(spl_token::id().to_bytes()),
+            owner_b: Some(owner_b.to_bytes()),
+        }
+        .pack()
+    };
+
+    let payer = context.payer.insecure_clone();
+    process_authority_instruction(
+        &mut context,
+        &payer,
+        accounts.clone(),
+        check_solvency(100, liability_owner),
+    )
+    .await
+    .unwrap();
+
+    let result = process_authority_instruction(
+        &mut context,
+        &payer,
+        accounts.clone(),
+        check_solvency(101, liability_owner),
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RecordError::ComparisonFailed as u32)
+        )
+    );
+
+    // a look-alike liability account of another program
+    let result = process_authority_instruction(
+        &mut context,
+        &payer,
+        accounts,
+        check_solvency(100, Pubkey::new_unique()),
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RecordError::IncorrectSourceOwner as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn check_source_mismatch_fail() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let mut program_test = ProgramTest::new("record", custom_program_id, None);
+    let read_account_pubkey = add_token_account(&mut program_test, 999, 0);
+    let other_account_pubkey = add_token_account(&mut program_test, 999, 999);
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[0u8; RecordData::SLOT_SIZE];
+    initialize_storage_account(
+        &mut context,
+        &authority,
+        &account,
+        &read_account_pubkey,
+        data,
+    )
+    .await;
+
+    // same value, but from another account or another offset
+    for (source, offset) in [(other_account_pubkey, 64), (other_account_pubkey, 121)] {
+        let accounts = vec![
+            AccountMeta::new(account.pubkey(), false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new_readonly(source, false),
+        ];
+        let result = process_authority_instruction(
+            &mut context,
+            &authority,
+            accounts,
+            RecordInstruction::CheckAdd {
+                slot: 0,
+                offset,
+                value_type: ValueType::U64,
+                encoding: Encoding::default(),
+                addition: 0,
+            }
+            .pack(),
+        )
+        .await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(RecordError::SourceMismatch as u32)
+            )
+        );
+    }
+}
+
+#[tokio::test]
+async fn check_unpinned_source_success() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let mut program_test = ProgramTest::new("record", custom_program_id, None);
+    let read_account_pubkey = add_token_account(&mut program_test, 999, 0);
+    let other_account_pubkey = add_token_account(&mut program_test, 1_000, 0);
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let data = &[0u8; RecordData::SLOT_SIZE];
+    initialize_storage_account(
+        &mut context,
+        &authority,
+        &account,
+        &read_account_pubkey,
+        data,
+    )
+    .await;
+
+    let write_accounts = vec![
+        AccountMeta::new(account.pubkey(), false),
+        AccountMeta::new_readonly(authority.pubkey(), true),
+        AccountMeta::new_readonly(read_account_pubkey, false),
+    ];
+    process_authority_instruction(
+        &mut context,
+        &authority,
+        write_accounts,
+        RecordInstruction::WriteU64 {
+            slot: 0,
+            offset: 64,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+            pin_source: false,
+        }
+        .pack(),
+    )
+    .await
+    .unwrap();
+
+    let check_accounts = vec![
+        AccountMeta::new(account.pubkey(), false),
+        AccountMeta::new_readonly(authority.pubkey(), true),
+        AccountMeta::new_readonly(other_account_pubkey, false),
+    ];
+    process_authority_instruction(
+        &mut context,
+        &authority,
+        check_accounts,
+        RecordInstruction::CheckAdd {
+            slot: 0,
+            offset: 64,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+            addition: 1,
+        }
+        .pack(),
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn write_incorrect_source_owner_fail() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let mut program_test = ProgramTest::new("record", custom_program_id, None);
+    let read_account_pubkey = add_token_account(&mut program_test, 999, 0);
+
+    // token account look-alike owned by an arbitrary program
+    let spoofed_pubkey = Pubkey::new_unique();
+    let mut spoofed_data = vec![0u8; TokenAccount::LEN];
+    spoofed_data[64..72].copy_from_slice(&u64::MAX.to_le_bytes());
+    program_test.add_account(
+        spoofed_pubkey,
+        Account {
+            lamports: 100,
+            data: spoofed_data,
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    create_storage_account(
+        &mut context,
+        &authority,
+        &account,
+        RecordData::SLOT_SIZE,
+        RecordInstruction::Initialize {
+            source_owner: Some(spl_token::id().to_bytes()),
+            token_mint: None,
+            token_owner: None,
+        },
+    )
+    .await;
+
+    let write = RecordInstruction::WriteU64 {
+        slot: 0,
+        offset: 64,
+        value_type: ValueType::U64,
+        encoding: Encoding::default(),
+        pin_source: false,
+    }
+    .pack();
+    let result = process_authority_instruction(
+        &mut context,
+        &authority,
+        vec![
+            AccountMeta::new(account.pubkey(), false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new_readonly(spoofed_pubkey, false),
+        ],
+        write.clone(),
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RecordError::IncorrectSourceOwner as u32)
+        )
+    );
+
+    process_authority_instruction(
+        &mut context,
+        &authority,
+        vec![
+            AccountMeta::new(account.pubkey(), false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new_readonly(read_account_pubkey, false),
+        ],
+        write,
+    )
+    .await
+    .unwrap();
+
+    let result = process_authority_instruction(
+        &mut context,
+        &authority,
+        vec![
+            AccountMeta::new(account.pubkey(), false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new_readonly(spoofed_pubkey, false),
+        ],
+        RecordInstruction::CheckAdd {
+            slot: 0,
+            offset: 64,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+            addition: 1,
+        }
+        .pack(),
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RecordError::IncorrectSourceOwner as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn check_token_add_success() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let mut program_test = ProgramTest::new("record", custom_program_id, None);
+    let mint = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let token_account_pubkey = add_token_account_with(&mut program_test, &mint, &owner, 999, 0);
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    create_storage_account(
+        &mut context,
+        &authority,
+        &account,
+        RecordData::SLOT_SIZE,
+        RecordInstruction::Initialize {
+            source_owner: None,
+            token_mint: Some(mint.to_bytes()),
+            token_owner: Some(owner.to_bytes()),
+        },
+    )
+    .await;
+
+    let accounts = vec![
+        AccountMeta::new(account.pubkey(), false),
+        AccountMeta::new_readonly(authority.pubkey(), true),
+        AccountMeta::new_readonly(token_account_pubkey, false),
+    ];
+    process_authority_instruction(
+        &mut context,
+        &authority,
+        accounts.clone(),
+        RecordInstruction::WriteTokenAmount { slot: 0 }.pack(),
+    )
+    .await
+    .unwrap();
+
+    let record_account = context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(read_slot(&record_account.data, 0), Value::Unsigned(999));
+
+    set_token_amount(&mut context, &token_account_pubkey, 1_000).await;
+    process_authority_instruction(
+        &mut context,
+        &authority,
+        accounts.clone(),
+        RecordInstruction::CheckTokenAdd {
+            slot: 0,
+            addition: 1,
+        }
+        .pack(),
+    )
+    .await
+    .unwrap();
+
+    let result = process_authority_instruction(
+        &mut context,
+        &authority,
+        accounts,
+        RecordInstruction::CheckTokenAdd {
+            slot: 0,
+            addition: 2,
+        }
+        .pack(),
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RecordError::InsufficientIncrease as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn write_token_amount_fail() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let mut program_test = ProgramTest::new("record", custom_program_id, None);
+    let mint = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let other_mint_pubkey =
+        add_token_account_with(&mut program_test, &Pubkey::new_unique(), &owner, 999, 0);
+    let other_owner_pubkey =
+        add_token_account_with(&mut program_test, &mint, &Pubkey::new_unique(), 999, 0);
+
+    // raw account with a token account layout, but not owned by the token program
+    let spoofed_pubkey = Pubkey::new_unique();
+    let mut spoofed_data = vec![0u8; TokenAccount::LEN];
+    spoofed_data[..32].copy_from_slice(mint.as_ref());
+    spoofed_data[32..64].copy_from_slice(owner.as_ref());
+    spoofed_data[108] = 1;
+    program_test.add_account(
+        spoofed_pubkey,
+        Account {
+            lamports: 100,
+            data: spoofed_data,
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    create_storage_account(
+        &mut context,
+        &authority,
+        &account,
+        RecordData::SLOT_SIZE,
+        RecordInstruction::Initialize {
+            source_owner: None,
+            token_mint: Some(mint.to_bytes()),
+            token_owner: Some(owner.to_bytes()),
+        },
+    )
+    .await;
+
+    for (source, error) in [
+        (other_mint_pubkey, RecordError::TokenMintMismatch),
+        (other_owner_pubkey, RecordError::TokenOwnerMismatch),
+        (spoofed_pubkey, RecordError::InvalidTokenAccount),
+    ] {
+        let accounts = vec![
+            AccountMeta::new(account.pubkey(), false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new_readonly(source, false),
+        ];
+        let result = process_authority_instruction(
+            &mut context,
+            &authority,
+            accounts,
+            RecordInstruction::WriteTokenAmount { slot: 0 }.pack(),
+        )
+        .await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+        );
+    }
+}
+
+#[tokio::test]
+async fn check_token_2022_add_success() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let mut program_test = ProgramTest::new("record", custom_program_id, None);
+    let mint = Pubkey::new_unique();
+    let owner = Pubkey::new_unique();
+    let token_account_pubkey = add_token_2022_account(&mut program_test, &mint, &owner, 999);
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    create_storage_account(
+        &mut context,
+        &authority,
+        &account,
+        RecordData::SLOT_SIZE,
+        RecordInstruction::Initialize {
+            source_owner: None,
+            token_mint: Some(mint.to_bytes()),
+            token_owner: Some(owner.to_bytes()),
+        },
+    )
+    .await;
+
+    let accounts = vec![
+        AccountMeta::new(account.pubkey(), false),
+        AccountMeta::new_readonly(authority.pubkey(), true),
+        AccountMeta::new_readonly(token_account_pubkey, false),
+    ];
+    process_authority_instruction(
+        &mut context,
+        &authority,
+        accounts.clone(),
+        RecordInstruction::WriteTokenAmount { slot: 0 }.pack(),
+    )
+    .await
+    .unwrap();
+
+    let record_account = context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(read_slot(&record_account.data, 0), Value::Unsigned(999));
+
+    set_token_amount(&mut context, &token_account_pubkey, 1_005).await;
+    process_authority_instruction(
+        &mut context,
+        &authority,
+        accounts,
+        RecordInstruction::CheckTokenAdd {
+            slot: 0,
+            addition: 6,
+        }
+        .pack(),
+    )
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn check_mint_supply_add() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let mut program_test = ProgramTest::new("record", custom_program_id, None);
+    let mint_pubkey = add_mint(&mut program_test, &spl_token::id(), 1_000);
+    let mint_2022_pubkey = add_mint(
+        &mut program_test,
+        &Pubkey::new_from_array(record::token::TOKEN_2022_PROGRAM_ID),
+        1_000,
+    );
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    create_storage_account(
+        &mut context,
+        &authority,
+        &account,
+        RecordData::SLOT_SIZE * 2,
+        RecordInstruction::Initialize {
+            source_owner: None,
+            token_mint: None,
+            token_owner: None,
+        },
+    )
+    .await;
+
+    for (slot, mint_pubkey) in [mint_pubkey, mint_2022_pubkey].into_iter().enumerate() {
+        let slot = slot as u8;
+        let accounts = vec![
+            AccountMeta::new(account.pubkey(), false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new_readonly(mint_pubkey, false),
+        ];
+        process_authority_instruction(
+            &mut context,
+            &authority,
+            accounts.clone(),
+            RecordInstruction::WriteMintSupply { slot }.pack(),
+        )
+        .await
+        .unwrap();
+
+        set_mint_supply(&mut context, &mint_pubkey, 1_500).await;
+        process_authority_instruction(
+            &mut context,
+            &authority,
+            accounts.clone(),
+            RecordInstruction::CheckMintSupplyAdd {
+                slot,
+                addition: 500,
+            }
+            .pack(),
+        )
+        .await
+        .unwrap();
+
+        let result = process_authority_instruction(
+            &mut context,
+            &authority,
+            accounts,
+            RecordInstruction::CheckMintSupplyAdd {
+                slot,
+                addition: 501,
+            }
+            .pack(),
+        )
+        .await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(RecordError::InsufficientIncrease as u32)
+            )
+        );
+    }
+}
+
+#[tokio::test]
+async fn check_lamports_add() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let program_test = ProgramTest::new("record", custom_program_id, None);
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    let wallet = Pubkey::new_unique();
+    create_storage_account(
+        &mut context,
+        &authority,
+        &account,
+        RecordData::SLOT_SIZE,
+        RecordInstruction::Initialize {
+            source_owner: None,
+            token_mint: None,
+            token_owner: None,
+        },
+    )
+    .await;
+    context.set_account(
+        &wallet,
+        &Account {
+            lamports: 1_000_000,
+            ..Account::default()
+        }
+        .into(),
+    );
+
+    let accounts = vec![
+        AccountMeta::new(account.pubkey(), false),
+        AccountMeta::new_readonly(authority.pubkey(), true),
+        AccountMeta::new_readonly(wallet, false),
+    ];
+    process_authority_instruction(
+        &mut context,
+        &authority,
+        accounts.clone(),
+        RecordInstruction::WriteLamports { slot: 0 }.pack(),
+    )
+    .await
+    .unwrap();
+
+    let record_account = context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        read_slot(&record_account.data, 0),
+        Value::Unsigned(1_000_000)
+    );
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[system_instruction::transfer(
+            &context.payer.pubkey(),
+            &wallet,
+            500,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    process_authority_instruction(
+        &mut context,
+        &authority,
+        accounts.clone(),
+        RecordInstruction::CheckLamportsAdd {
+            slot: 0,
+            addition: 500,
+        }
+        .pack(),
+    )
+    .await
+    .unwrap();
+
+    let result = process_authority_instruction(
+        &mut context,
+        &authority,
+        accounts.clone(),
+        RecordInstruction::CheckLamportsAdd {
+            slot: 0,
+            addition: 501,
+        }
+        .pack(),
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RecordError::InsufficientIncrease as u32)
+        )
+    );
+
+    // a lamport snapshot can't be checked against account data
+    let result = process_authority_instruction(
+        &mut context,
+        &authority,
+        accounts,
+        RecordInstruction::CheckAdd {
+            slot: 0,
+            offset: 0,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+            addition: 0,
+        }
+        .pack(),
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RecordError::SourceMismatch as u32)
+        )
+    );
+}
+
+async fn process_initialize_pda(
+    context: &mut ProgramTestContext,
+    authority: &Keypair,
+    record: &Pubkey,
+    seed: &[u8],
+    bump: u8,
+) -> Result<(), BanksClientError> {
+    process_authority_instruction(
+        context,
+        authority,
+        vec![
+            AccountMeta::new(*record, false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new(context.payer.pubkey(), true),
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+        ],
+        RecordInstruction::InitializePda {
+            slot_count: 2,
+            seed: seed.to_vec(),
+            bump,
+            source_owner: None,
+            token_mint: None,
+            token_owner: None,
+        }
+        .pack(),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn initialize_pda_success() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let program_test = ProgramTest::new("record", custom_program_id, None);
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    for seed in [&b"first"[..], &b"prefunded"[..]] {
+        let (record, bump) = Pubkey::find_program_address(
+            &[RecordData::SEED_PREFIX, authority.pubkey().as_ref(), seed],
+            &custom_program_id,
+        );
+        if seed == b"prefunded" {
+            // lamports sent to the address before creation must not block it
+            context.set_account(
+                &record,
+                &Account {
+                    lamports: 1,
+                    ..Account::default()
+                }
+                .into(),
+            );
+        }
+
+        process_initialize_pda(&mut context, &authority, &record, seed, bump)
+            .await
+            .unwrap();
+
+        let record_account = context
+            .banks_client
+            .get_account(record)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(record_account.owner, custom_program_id);
+        assert_eq!(record_account.data.len(), RecordData::account_len(2));
+        assert_eq!(
+            record_account.lamports,
+            Rent::default().minimum_balance(RecordData::account_len(2))
+        );
+        let header = bytemuck::try_from_bytes::<RecordData>(
+            &record_account.data[..RecordData::WRITABLE_START_INDEX],
+        )
+        .unwrap();
+        assert!(header.is_initialized());
+        assert_eq!(header.authority, authority.pubkey().to_bytes());
+        assert_eq!(header.slot_count, 2);
+        assert_eq!(header.bump(), Some(bump));
+    }
+}
+
+#[tokio::test]
+async fn initialize_pda_fail() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let program_test = ProgramTest::new("record", custom_program_id, None);
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let (record, bump) = Pubkey::find_program_address(
+        &[
+            RecordData::SEED_PREFIX,
+            authority.pubkey().as_ref(),
+            b"seed",
+        ],
+        &custom_program_id,
+    );
+
+    // another seed, or a bump that does not derive the record address
+    for (seed, bump) in [(&b"other"[..], bump), (&b"seed"[..], bump.wrapping_add(1))] {
+        let result = process_initialize_pda(&mut context, &authority, &record, seed, bump).await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(0, InstructionError::InvalidSeeds)
+        );
+    }
+
+    // a lower bump derives another address, which would be a second record
+    // for the same authority and seed
+    let (other_record, other_bump) = (0..bump)
+        .rev()
+        .find_map(|other_bump| {
+            Pubkey::create_program_address(
+                &[
+                    RecordData::SEED_PREFIX,
+                    authority.pubkey().as_ref(),
+                    b"seed",
+                    &[other_bump],
+                ],
+                &custom_program_id,
+            )
+            .ok()
+            .map(|address| (address, other_bump))
+        })
+        .unwrap();
+    let result =
+        process_initialize_pda(&mut context, &authority, &other_record, b"seed", other_bump).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidSeeds)
+    );
+
+    process_initialize_pda(&mut context, &authority, &record, b"seed", bump)
+        .await
+        .unwrap();
+
+    // a fresh blockhash so the retry is not a duplicate transaction
+    context.last_blockhash = context
+        .banks_client
+        .get_new_latest_blockhash(&context.last_blockhash)
+        .await
+        .unwrap();
+    let result = process_initialize_pda(&mut context, &authority, &record, b"seed", bump).await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized)
+    );
+}
+
+#[tokio::test]
+async fn record_not_owned_by_program_fail() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let program_test = ProgramTest::new("record", custom_program_id, None);
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    // a well-formed record with a lower stored value, owned by another program
+    let authority = Keypair::new();
+    let forged = Pubkey::new_unique();
+    let source = Pubkey::new_unique();
+    let mut data = vec![0u8; RecordData::account_len(1)];
+    let header =
+        bytemuck::from_bytes_mut::<RecordData>(&mut data[..RecordData::WRITABLE_START_INDEX]);
+    header.version = RecordData::CURRENT_VERSION;
+    header.authority = authority.pubkey().to_bytes();
+    header.slot_count = 1;
+    context.set_account(
+        &forged,
+        &Account {
+            lamports: 1_000_000_000,
+            data,
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        }
+        .into(),
+    );
+    context.set_account(
+        &source,
+        &Account {
+            lamports: 1,
+            data: vec![0u8; 8],
+            ..Account::default()
+        }
+        .into(),
+    );
+
+    let instructions = [
+        RecordInstruction::Initialize {
+            source_owner: None,
+            token_mint: None,
+            token_owner: None,
+        },
+        RecordInstruction::CheckAdd {
+            slot: 0,
+            offset: 0,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+            addition: 0,
+        },
+        RecordInstruction::WriteLamports { slot: 0 },
+        RecordInstruction::SetAuthority,
+        RecordInstruction::CloseAccount,
+    ];
+    for instruction in instructions {
+        let result = process_authority_instruction(
+            &mut context,
+            &authority,
+            vec![
+                AccountMeta::new(forged, false),
+                AccountMeta::new_readonly(authority.pubkey(), true),
+                AccountMeta::new(source, false),
+            ],
+            instruction.pack(),
+        )
+        .await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(RecordError::IncorrectRecordOwner as u32)
+            )
+        );
+    }
+}
+
+#[tokio::test]
+async fn legacy_record_version_fail() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let program_test = ProgramTest::new("record", custom_program_id, None);
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    // a record of the single-value layout, large enough to be misread as a
+    // current one
+    let authority = Keypair::new();
+    let legacy = Pubkey::new_unique();
+    let source = Pubkey::new_unique();
+    let mut data = vec![0u8; RecordData::account_len(1)];
+    data[0] = 1;
+    data[1..33].copy_from_slice(authority.pubkey().as_ref());
+    data[33] = 5;
+    context.set_account(
+        &legacy,
+        &Account {
+            lamports: 1_000_000_000,
+            data,
+            owner: custom_program_id,
+            executable: false,
+            rent_epoch: 0,
+        }
+        .into(),
+    );
+    context.set_account(
+        &source,
+        &Account {
+            lamports: 1,
+            data: vec![0u8; 8],
+            ..Account::default()
+        }
+        .into(),
+    );
+
+    let cases = [
+        (
+            RecordInstruction::Initialize {
+                source_owner: None,
+                token_mint: None,
+                token_owner: None,
+            },
+            InstructionError::AccountAlreadyInitialized,
+        ),
+        (
+            RecordInstruction::WriteLamports { slot: 0 },
+            InstructionError::UninitializedAccount,
+        ),
+    ];
+    for (instruction, error) in cases {
+        let result = process_authority_instruction(
+            &mut context,
+            &authority,
+            vec![
+                AccountMeta::new(legacy, false),
+                AccountMeta::new_readonly(authority.pubkey(), true),
+                AccountMeta::new(source, false),
+            ],
+            instruction.pack(),
+        )
+        .await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(0, error)
+        );
+    }
+}
+
+#[tokio::test]
+async fn legacy_record_close_success() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let program_test = ProgramTest::new("record", custom_program_id, None);
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    // a record of the single-value layout: version, authority and a u64
+    let authority = Keypair::new();
+    let new_authority = Keypair::new();
+    let legacy = Pubkey::new_unique();
+    let mut data = vec![0u8; RecordData::LEGACY_HEADER_LEN + 8];
+    data[0] = RecordData::LEGACY_VERSION;
+    data[1..33].copy_from_slice(authority.pubkey().as_ref());
+    data[33..].copy_from_slice(&999u64.to_le_bytes());
+    let lamports = Rent::default().minimum_balance(data.len());
+    context.set_account(
+        &legacy,
+        &Account {
+            lamports,
+            data,
+            owner: custom_program_id,
+            executable: false,
+            rent_epoch: 0,
+        }
+        .into(),
+    );
+
+    process_authority_instruction(
+        &mut context,
+        &authority,
+        vec![
+            AccountMeta::new(legacy, false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new_readonly(new_authority.pubkey(), false),
+        ],
+        RecordInstruction::SetAuthority.pack(),
+    )
+    .await
+    .unwrap();
+    let record_account = context
+        .banks_client
+        .get_account(legacy)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(&record_account.data[1..33], new_authority.pubkey().as_ref());
+
+    let destination = Pubkey::new_unique();
+    process_authority_instruction(
+        &mut context,
+        &new_authority,
+        vec![
+            AccountMeta::new(legacy, false),
+            AccountMeta::new_readonly(new_authority.pubkey(), true),
+            AccountMeta::new(destination, false),
+        ],
+        RecordInstruction::CloseAccount.pack(),
+    )
+    .await
+    .unwrap();
+    assert!(context
+        .banks_client
+        .get_account(legacy)
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        context.banks_client.get_balance(destination).await.unwrap(),
+        lamports
+    );
+}
+
+#[tokio::test]
+async fn incorrect_program_id_fail() {
+    // the same binary, deployed at an address it was not built for
+    let other_program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new("record", other_program_id, None);
+    let context: ProgramTestContext = program_test.start_with_context().await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[record::client::read_u64(
+            &other_program_id,
+            &Pubkey::new_unique(),
+            0,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    assert_eq!(
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::IncorrectProgramId)
+    );
+}
+
+#[tokio::test]
+async fn close_account_revival_fail() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let mut program_test = ProgramTest::new("record", custom_program_id, None);
+    let read_account_pubkey = add_token_account(&mut program_test, 999, 0);
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    initialize_storage_account(
+        &mut context,
+        &authority,
+        &account,
+        &read_account_pubkey,
+        &[0u8; RecordData::SLOT_SIZE],
+    )
+    .await;
+
+    let record_ix = |instruction: RecordInstruction| Instruction {
+        program_id: custom_program_id,
+        accounts: vec![
+            AccountMeta::new(account.pubkey(), false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new(read_account_pubkey, false),
+        ],
+        data: instruction.pack(),
+    };
+    let close_ix = Instruction {
+        program_id: custom_program_id,
+        accounts: vec![
+            AccountMeta::new(account.pubkey(), false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new(authority.pubkey(), false),
+        ],
+        data: RecordInstruction::CloseAccount.pack(),
+    };
+    // tops the closed record up so it outlives the transaction
+    let revive_ix = system_instruction::transfer(
+        &context.payer.pubkey(),
+        &account.pubkey(),
+        Rent::default().minimum_balance(RecordData::CLOSED_LEN),
+    );
+
+    let attacks = [
+        RecordInstruction::CheckAdd {
+            slot: 0,
+            offset: 64,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+            addition: 0,
+        },
+        RecordInstruction::WriteU64 {
+            slot: 0,
+            offset: 64,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+            pin_source: false,
+        },
+        RecordInstruction::Initialize {
+            source_owner: None,
+            token_mint: None,
+            token_owner: None,
+        },
+    ];
+    for attack in attacks.clone() {
+        let transaction = Transaction::new_signed_with_payer(
+            &[close_ix.clone(), revive_ix.clone(), record_ix(attack)],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &authority],
+            context.last_blockhash,
+        );
+        assert_eq!(
+            context
+                .banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(
+                2,
+                InstructionError::Custom(RecordError::RecordClosed as u32)
+            )
+        );
+    }
+
+    // a revived record keeps only the closed marker
+    let transaction = Transaction::new_signed_with_payer(
+        &[close_ix, revive_ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let record_account = context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(record_account.owner, custom_program_id);
+    assert_eq!(record_account.data, vec![RecordData::CLOSED_VERSION]);
+
+    for attack in attacks {
+        let transaction = Transaction::new_signed_with_payer(
+            &[record_ix(attack)],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &authority],
+            context.last_blockhash,
+        );
+        assert_eq!(
+            context
+                .banks_client
+                .process_transaction(transaction)
+                .await
+                .unwrap_err()
+                .unwrap(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(RecordError::RecordClosed as u32)
+            )
+        );
+    }
+}
+
+#[tokio::test]
+async fn write_offset_out_of_bounds_fail() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let mut program_test = ProgramTest::new("record", custom_program_id, None);
+    let read_account_pubkey = add_token_account(&mut program_test, 999, 0);
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    initialize_storage_account(
+        &mut context,
+        &authority,
+        &account,
+        &read_account_pubkey,
+        &[0u8; RecordData::SLOT_SIZE],
+    )
+    .await;
+
+    for (offset, error) in [
+        (TokenAccount::LEN as u64, RecordError::OffsetOutOfBounds),
+        (u64::MAX, RecordError::OffsetOutOfBounds),
+        (TokenAccount::LEN as u64 - 4, RecordError::SourceTooSmall),
+    ] {
+        let result = process_authority_instruction(
+            &mut context,
+            &authority,
+            vec![
+                AccountMeta::new(account.pubkey(), false),
+                AccountMeta::new_readonly(authority.pubkey(), true),
+                AccountMeta::new_readonly(read_account_pubkey, false),
+            ],
+            RecordInstruction::WriteU64 {
+                slot: 0,
+                offset,
+                value_type: ValueType::U64,
+                encoding: Encoding::default(),
+                pin_source: true,
+            }
+            .pack(),
+        )
+        .await;
+        assert_eq!(
+            result.unwrap_err().unwrap(),
+            TransactionError::InstructionError(0, InstructionError::Custom(error as u32))
+        );
+    }
+}
+
+/// Processes an instruction signed by `authority`, returning the record
+/// events it logged
+async fn process_logging_events(
+    context: &mut ProgramTestContext,
+    authority: &Keypair,
+    accounts: Vec<AccountMeta>,
+    data: Vec<u8>,
+) -> Vec<RecordEvent> {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    let ix = Instruction {
+        program_id: Pubkey::new_from_array(record::ID),
+        accounts,
+        data,
+    };
+    let transaction = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, authority],
+        context.last_blockhash,
+    );
+    let result = context
+        .banks_client
+        .process_transaction_with_metadata(transaction)
+        .await
+        .unwrap();
+    result.result.unwrap();
+    result
+        .metadata
+        .unwrap()
+        .log_messages
+        .iter()
+        .filter_map(|log| log.strip_prefix("Program data: "))
+        .map(|data| RecordEvent::unpack(&STANDARD.decode(data).unwrap()).unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn events_logged() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let mut program_test = ProgramTest::new("record", custom_program_id, None);
+    let read_account_pubkey = add_token_account(&mut program_test, 999, 0);
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let new_authority = Keypair::new();
+    let account = Keypair::new();
+    create_storage_account(
+        &mut context,
+        &authority,
+        &account,
+        RecordData::SLOT_SIZE,
+        RecordInstruction::Initialize {
+            source_owner: None,
+            token_mint: None,
+            token_owner: None,
+        },
+    )
+    .await;
+    let record = account.pubkey().to_bytes();
+
+    let events = process_logging_events(
+        &mut context,
+        &authority,
+        vec![
+            AccountMeta::new(account.pubkey(), false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new_readonly(read_account_pubkey, false),
+        ],
+        RecordInstruction::WriteU64 {
+            slot: 0,
+            offset: 64,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+            pin_source: true,
+        }
+        .pack(),
+    )
+    .await;
+    assert_eq!(
+        events,
+        vec![RecordEvent::Write {
+            record,
+            slot: 0,
+            value_type: ValueType::U64,
+            value: Value::Unsigned(999),
+            source: read_account_pubkey.to_bytes(),
+            offset: 64,
+            pinned: true,
+        }]
+    );
+
+    let events = process_logging_events(
+        &mut context,
+        &authority,
+        vec![
+            AccountMeta::new(account.pubkey(), false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new_readonly(new_authority.pubkey(), false),
+        ],
+        RecordInstruction::SetAuthority.pack(),
+    )
+    .await;
+    assert_eq!(
+        events,
+        vec![RecordEvent::SetAuthority {
+            record,
+            old_authority: authority.pubkey().to_bytes(),
+            new_authority: new_authority.pubkey().to_bytes(),
+        }]
+    );
+
+    let lamports = context
+        .banks_client
+        .get_account(account.pubkey())
+        .await
+        .unwrap()
+        .unwrap()
+        .lamports;
+    let events = process_logging_events(
+        &mut context,
+        &new_authority,
+        vec![
+            AccountMeta::new(account.pubkey(), false),
+            AccountMeta::new_readonly(new_authority.pubkey(), true),
+            AccountMeta::new(authority.pubkey(), false),
+        ],
+        RecordInstruction::CloseAccount.pack(),
+    )
+    .await;
+    assert_eq!(
+        events,
+        vec![RecordEvent::Close {
+            record,
+            destination: authority.pubkey().to_bytes(),
+            lamports,
+        }]
+    );
+}
+
+/// Processes an instruction signed by `authority`, returning its return data
+/// padded to `len`, as the runtime trims trailing zeros
+async fn process_returning_data(
+    context: &mut ProgramTestContext,
+    authority: &Keypair,
+    accounts: Vec<AccountMeta>,
+    data: Vec<u8>,
+    len: usize,
+) -> Vec<u8> {
+    let ix = Instruction {
+        program_id: Pubkey::new_from_array(record::ID),
+        accounts,
+        data,
+    };
+    let transaction = Transaction::new_signed_with_payer(
+        &[ix],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, authority],
+        context.last_blockhash,
+    );
+    let result = context
+        .banks_client
+        .process_transaction_with_metadata(transaction)
+        .await
+        .unwrap();
+    result.result.unwrap();
+    let mut data = match result.metadata.unwrap().return_data {
+        Some(return_data) => {
+            assert_eq!(return_data.program_id, Pubkey::new_from_array(record::ID));
+            return_data.data
+        }
+        None => Vec::new(),
+    };
+    data.resize(len, 0);
+    data
+}
+
+#[tokio::test]
+async fn check_add_and_read_return_data() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let mut program_test = ProgramTest::new("record", custom_program_id, None);
+    let read_account_pubkey = add_token_account(&mut program_test, 999, 0);
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    initialize_storage_account(
+        &mut context,
+        &authority,
+        &account,
+        &read_account_pubkey,
+        &[0u8; RecordData::SLOT_SIZE],
+    )
+    .await;
+
+    let return_data = process_returning_data(
+        &mut context,
+        &authority,
+        vec![AccountMeta::new_readonly(account.pubkey(), false)],
+        RecordInstruction::ReadU64 { slot: 0 }.pack(),
+        17,
+    )
+    .await;
+    let mut expected = vec![ValueType::U64 as u8];
+    expected.extend_from_slice(&999u128.to_le_bytes());
+    assert_eq!(return_data, expected);
+
+    set_token_amount(&mut context, &read_account_pubkey, 1_250).await;
+    let return_data = process_returning_data(
+        &mut context,
+        &authority,
+        vec![
+            AccountMeta::new_readonly(account.pubkey(), false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new_readonly(read_account_pubkey, false),
+        ],
+        RecordInstruction::CheckAdd {
+            slot: 0,
+            offset: 64,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+            addition: 200,
+        }
+        .pack(),
+        16,
+    )
+    .await;
+    assert_eq!(return_data, 251u128.to_le_bytes().to_vec());
+
+    // 999 plus 20% requires 1_198, the increase is the same as above
+    let return_data = process_returning_data(
+        &mut context,
+        &authority,
+        vec![
+            AccountMeta::new_readonly(account.pubkey(), false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new_readonly(read_account_pubkey, false),
+        ],
+        RecordInstruction::CheckAddBps {
+            slot: 0,
+            offset: 64,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+            bps: 2_000,
+        }
+        .pack(),
+        16,
+    )
+    .await;
+    assert_eq!(return_data, 251u128.to_le_bytes().to_vec());
+}
+
+#[tokio::test]
+async fn check_add_overflow_fail() {
+    let custom_program_id = Pubkey::new_from_array(record::ID);
+    let mut program_test = ProgramTest::new("record", custom_program_id, None);
+    let read_account_pubkey = add_token_account(&mut program_test, 999, 0);
+    let mut context: ProgramTestContext = program_test.start_with_context().await;
+
+    let authority = Keypair::new();
+    let account = Keypair::new();
+    initialize_storage_account(
+        &mut context,
+        &authority,
+        &account,
+        &read_account_pubkey,
+        &[0u8; RecordData::SLOT_SIZE],
+    )
+    .await;
+
+    // 999 + u64::MAX wraps to 998 with unchecked u64 arithmetic
+    set_token_amount(&mut context, &read_account_pubkey, u64::MAX).await;
+    let result = process_authority_instruction(
+        &mut context,
+        &authority,
+        vec![
+            AccountMeta::new_readonly(account.pubkey(), false),
+            AccountMeta::new_readonly(authority.pubkey(), true),
+            AccountMeta::new_readonly(read_account_pubkey, false),
+        ],
+        RecordInstruction::CheckAdd {
+            slot: 0,
+            offset: 64,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+            addition: u64::MAX,
+        }
+        .pack(),
+    )
+    .await;
+    assert_eq!(
+        result.unwrap_err().unwrap(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(RecordError::Overflow as u32)
+        )
+    );
+}
@@ -0,0 +1,567 @@
+//! Keeps `idl/record.json` in sync with the program: every instruction,
+//! account, type and error in the IDL is encoded from sample values and
+//! compared with the bytes produced by the crate.
+
+use std::str::FromStr;
+
+use num_traits::FromPrimitive;
+use record::{
+    client::{self, RecordConfig, SourceValue},
+    error::RecordError,
+    event::RecordEvent,
+    instruction::{Comparison, RecordInstruction},
+    state::{RecordData, RecordSlot},
+    value::{BitField, Encoding, Value, ValueType},
+};
+use serde_json::{json, Value as Json};
+use solana_instruction::Instruction;
+use solana_pubkey::Pubkey;
+
+const IDL: &str = include_str!("../idl/record.json");
+
+struct Idl(Json);
+
+impl Idl {
+    fn load() -> Self {
+        Self(serde_json::from_str(IDL).unwrap())
+    }
+
+    fn program(&self) -> &Json {
+        &self.0["program"]
+    }
+
+    fn find<'a>(&'a self, list: &str, name: &str) -> &'a Json {
+        self.program()[list]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|node| node["name"] == name)
+            .unwrap_or_else(|| panic!("{name} missing from {list}"))
+    }
+
+    fn instruction(&self, name: &str) -> &Json {
+        self.find("instructions", name)
+    }
+
+    /// Encodes `value` the way a Codama client would serialize `ty`
+    fn encode(&self, ty: &Json, value: &Json, buf: &mut Vec<u8>) {
+        match ty["kind"].as_str().unwrap() {
+            "numberTypeNode" => {
+                assert_eq!(ty["endian"], "le");
+                let number = match value {
+                    Json::String(number) => number.parse::<u128>().unwrap(),
+                    number => number.as_u64().unwrap() as u128,
+                };
+                let size = match ty["format"].as_str().unwrap() {
+                    "u8" => 1,
+                    "u16" => 2,
+                    "u32" => 4,
+                    "u64" => 8,
+                    "u128" => 16,
+                    format => panic!("unsupported number format {format}"),
+                };
+                assert!(size == 16 || number < 1 << (size * 8), "{number} overflows");
+                buf.extend_from_slice(&number.to_le_bytes()[..size]);
+            }
+            "booleanTypeNode" => {
+                let flag = json!(value.as_bool().unwrap() as u8);
+                self.encode(&ty["size"], &flag, buf);
+            }
+            "publicKeyTypeNode" => {
+                let key = Pubkey::from_str(value.as_str().unwrap()).unwrap();
+                buf.extend_from_slice(key.as_ref());
+            }
+            "bytesTypeNode" => {
+                for byte in value.as_array().unwrap() {
+                    buf.push(byte.as_u64().unwrap() as u8);
+                }
+            }
+            "optionTypeNode" => {
+                assert_eq!(ty["fixed"], false);
+                let present = json!(!value.is_null() as u8);
+                self.encode(&ty["prefix"], &present, buf);
+                if !value.is_null() {
+                    self.encode(&ty["item"], value, buf);
+                }
+            }
+            "sizePrefixTypeNode" => {
+                let mut inner = vec![];
+                self.encode(&ty["type"], value, &mut inner);
+                self.encode(&ty["prefix"], &json!(inner.len()), buf);
+                buf.extend(inner);
+            }
+            "fixedSizeTypeNode" => {
+                let start = buf.len();
+                self.encode(&ty["type"], value, buf);
+                assert_eq!(buf.len() - start, ty["size"].as_u64().unwrap() as usize);
+            }
+            "arrayTypeNode" => {
+                assert_eq!(ty["count"]["kind"], "remainderCountNode");
+                for item in value.as_array().unwrap() {
+                    self.encode(&ty["item"], item, buf);
+                }
+            }
+            "definedTypeLinkNode" => {
+                let defined = self.find("definedTypes", ty["name"].as_str().unwrap());
+                self.encode(&defined["type"], value, buf);
+            }
+            "structTypeNode" => {
+                let fields = ty["fields"].as_array().unwrap();
+                assert_eq!(fields.len(), value.as_object().unwrap().len());
+                for field in fields {
+                    let name = field["name"].as_str().unwrap();
+                    let value = value
+                        .get(name)
+                        .unwrap_or_else(|| panic!("missing field {name}"));
+                    self.encode(&field["type"], value, buf);
+                }
+            }
+            "enumTypeNode" => {
+                // empty variants are strings, struct variants single-key objects
+                let (name, fields) = match value {
+                    Json::String(name) => (name.as_str(), None),
+                    Json::Object(variant) => {
+                        let (name, fields) = variant.iter().next().unwrap();
+                        (name.as_str(), Some(fields))
+                    }
+                    _ => panic!("invalid enum value {value}"),
+                };
+                let variants = ty["variants"].as_array().unwrap();
+                let index = variants
+                    .iter()
+                    .position(|variant| variant["name"] == name)
+                    .unwrap_or_else(|| panic!("unknown variant {name}"));
+                self.encode(&ty["size"], &json!(index), buf);
+                let variant = &variants[index];
+                match variant["kind"].as_str().unwrap() {
+                    "enumEmptyVariantTypeNode" => assert!(fields.is_none()),
+                    "enumStructVariantTypeNode" => {
+                        self.encode(&variant["struct"], fields.unwrap(), buf)
+                    }
+                    kind => panic!("unsupported variant {kind}"),
+                }
+            }
+            kind => panic!("unsupported type {kind}"),
+        }
+    }
+
+    /// Encodes the data of an instruction, the discriminator is added from
+    /// its default value
+    fn encode_instruction(&self, name: &str, args: Json) -> Vec<u8> {
+        let instruction = self.instruction(name);
+        let mut buf = vec![];
+        for argument in instruction["arguments"].as_array().unwrap() {
+            let value = match argument["defaultValueStrategy"].as_str() {
+                Some("omitted") => &argument["defaultValue"]["number"],
+                _ => args
+                    .get(argument["name"].as_str().unwrap())
+                    .unwrap_or_else(|| panic!("{name} is missing {}", argument["name"])),
+            };
+            self.encode(&argument["type"], value, &mut buf);
+        }
+        buf
+    }
+}
+
+fn key(pubkey: &Pubkey) -> Json {
+    json!(pubkey.to_string())
+}
+
+#[test]
+fn instructions_match_pack() {
+    let idl = Idl::load();
+    let program_id = Pubkey::new_unique();
+    let record = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let source = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let bit_field = SourceValue {
+        offset: 8,
+        value_type: ValueType::I32,
+        encoding: Encoding {
+            big_endian: true,
+            bit_field: Some(BitField {
+                mask: u128::MAX >> 1,
+                shift: 3,
+            }),
+        },
+    };
+    let bit_field_args = json!({
+        "slot": 2,
+        "offset": 8,
+        "valueType": "i32",
+        "encoding": {
+            "bigEndianBitField": { "mask": (u128::MAX >> 1).to_string(), "shift": 3 }
+        },
+    });
+    let config = RecordConfig {
+        source_owner: None,
+        token_mint: Some(mint),
+        token_owner: Some(authority),
+    };
+    let config_args = json!({
+        "sourceOwner": null,
+        "tokenMint": key(&mint),
+        "tokenOwner": key(&authority),
+    });
+    let with = |base: &Json, extra: Json| {
+        let mut args = base.clone();
+        for (name, value) in extra.as_object().unwrap() {
+            args[name] = value.clone();
+        }
+        args
+    };
+
+    let (_, bump) = client::find_record_address(&program_id, &authority, b"vault");
+
+    let cases: Vec<(&str, Json, Instruction)> = vec![
+        (
+            "initialize",
+            config_args.clone(),
+            client::initialize_with_config(&program_id, &record, &authority, &config),
+        ),
+        (
+            "writeU64",
+            json!({
+                "slot": 0,
+                "offset": 64,
+                "valueType": "u64",
+                "encoding": "littleEndian",
+                "pinSource": true,
+            }),
+            client::write_u64(
+                &program_id,
+                &record,
+                &authority,
+                &source,
+                0,
+                SourceValue::u64(64),
+                true,
+            ),
+        ),
+        (
+            "checkAdd",
+            with(&bit_field_args, json!({ "addition": 42 })),
+            client::check_add(&program_id, &record, &authority, &source, 2, bit_field, 42),
+        ),
+        (
+            "setAuthority",
+            json!({}),
+            client::set_authority(&program_id, &record, &authority, &source),
+        ),
+        (
+            "closeAccount",
+            json!({}),
+            client::close_account(&program_id, &record, &authority, &source),
+        ),
+        (
+            "checkSub",
+            with(
+                &bit_field_args,
+                json!({ "minDecrease": 1, "maxDecrease": u64::MAX }),
+            ),
+            client::check_sub(
+                &program_id,
+                &record,
+                &authority,
+                &source,
+                2,
+                bit_field,
+                1,
+                u64::MAX,
+            ),
+        ),
+        (
+            "checkCompare",
+            with(
+                &bit_field_args,
+                json!({ "op": { "range": { "upper": 9 } }, "operand": 7 }),
+            ),
+            client::check_compare(
+                &program_id,
+                &record,
+                &authority,
+                &source,
+                2,
+                bit_field,
+                Comparison::Range { upper: 9 },
+                7,
+            ),
+        ),
+        (
+            "checkAddBps",
+            with(&bit_field_args, json!({ "bps": 30 })),
+            client::check_add_bps(&program_id, &record, &authority, &source, 2, bit_field, 30),
+        ),
+        (
+            "checkCompareAccounts",
+            json!({
+                "offsetA": 64,
+                "offsetB": 72,
+                "valueType": "u64",
+                "encoding": "bigEndian",
+                "op": "ge",
+                "operand": 5,
+                "ownerA": key(&mint),
+                "ownerB": null,
+            }),
+            client::check_compare_accounts(
+                &program_id,
+                &record,
+                &source,
+                SourceValue {
+                    encoding: Encoding {
+                        big_endian: true,
+                        bit_field: None,
+                    },
+                    ..SourceValue::u64(64)
+                },
+                72,
+                Comparison::Ge,
+                5,
+                Some(mint),
+                None,
+            ),
+        ),
+        (
+            "writeTokenAmount",
+            json!({ "slot": 1 }),
+            client::write_token_amount(&program_id, &record, &authority, &source, 1),
+        ),
+        (
+            "checkTokenAdd",
+            json!({ "slot": 1, "addition": 5 }),
+            client::check_token_add(&program_id, &record, &authority, &source, 1, 5),
+        ),
+        (
+            "writeMintSupply",
+            json!({ "slot": 2 }),
+            client::write_mint_supply(&program_id, &record, &authority, &mint, 2),
+        ),
+        (
+            "checkMintSupplyAdd",
+            json!({ "slot": 2, "addition": 6 }),
+            client::check_mint_supply_add(&program_id, &record, &authority, &mint, 2, 6),
+        ),
+        (
+            "writeLamports",
+            json!({ "slot": 3 }),
+            client::write_lamports(&program_id, &record, &authority, &source, 3),
+        ),
+        (
+            "checkLamportsAdd",
+            json!({ "slot": 3, "addition": 7 }),
+            client::check_lamports_add(&program_id, &record, &authority, &source, 3, 7),
+        ),
+        (
+            "initializePda",
+            with(
+                &config_args,
+                json!({ "slotCount": 4, "seed": b"vault".to_vec(), "bump": bump }),
+            ),
+            client::initialize_pda(&program_id, &authority, &source, b"vault", 4, &config).unwrap(),
+        ),
+        (
+            "readU64",
+            json!({ "slot": 5 }),
+            client::read_u64(&program_id, &record, 5),
+        ),
+    ];
+
+    let instructions = idl.program()["instructions"].as_array().unwrap();
+    assert_eq!(instructions.len(), cases.len());
+    for (tag, (name, args, instruction)) in cases.into_iter().enumerate() {
+        assert_eq!(instructions[tag]["name"], name);
+        let data = idl.encode_instruction(name, args);
+        assert_eq!(data, instruction.data, "{name}");
+        assert_eq!(data[0] as usize, tag);
+        assert!(RecordInstruction::unpack(&data).is_ok());
+
+        let accounts = idl.instruction(name)["accounts"].as_array().unwrap();
+        assert_eq!(accounts.len(), instruction.accounts.len(), "{name}");
+        for (account, meta) in accounts.iter().zip(&instruction.accounts) {
+            assert_eq!(account["isWritable"], meta.is_writable, "{name}");
+            assert_eq!(account["isSigner"], meta.is_signer, "{name}");
+            if let Some(default) = account.get("defaultValue") {
+                assert_eq!(default["publicKey"], key(&meta.pubkey));
+            }
+        }
+    }
+    // no tags past the ones in the IDL
+    assert!(RecordInstruction::unpack(&[instructions.len() as u8]).is_err());
+}
+
+#[test]
+fn value_types_match() {
+    let idl = Idl::load();
+    let value_type = &idl.find("definedTypes", "valueType")["type"];
+    let variants = value_type["variants"].as_array().unwrap();
+    for (tag, variant) in variants.iter().enumerate() {
+        let parsed = ValueType::from_u8(tag as u8).unwrap();
+        assert_eq!(variant["name"], parsed.to_string());
+    }
+    assert!(ValueType::from_u8(variants.len() as u8).is_none());
+}
+
+#[test]
+fn record_account_matches_layout() {
+    let idl = Idl::load();
+    let authority = Pubkey::new_unique();
+    let source = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+
+    let mut header = RecordData {
+        version: RecordData::CURRENT_VERSION,
+        authority: authority.to_bytes(),
+        slot_count: 2,
+        flags: 0,
+        source_owner: [0; 32],
+        token_mint: [0; 32],
+        token_owner: [0; 32],
+        bump: 0,
+    };
+    header.set_token_mint(Some(&mint.to_bytes()));
+    header.set_bump(254);
+    let pinned = RecordSlot::new(
+        ValueType::I16,
+        Value::Signed(-2),
+        &source.to_bytes(),
+        64,
+        true,
+    );
+    let lamports = RecordSlot::new(
+        ValueType::U64,
+        Value::Unsigned(1_000_000),
+        &source.to_bytes(),
+        RecordSlot::LAMPORTS_OFFSET,
+        false,
+    );
+
+    let mut expected = bytemuck::bytes_of(&header).to_vec();
+    expected.extend_from_slice(bytemuck::bytes_of(&pinned));
+    expected.extend_from_slice(bytemuck::bytes_of(&lamports));
+
+    let slot = |value_type: &str, value: Value, flags: u8, offset: u64| {
+        json!({
+            "valueType": value_type,
+            "value": value.encode().to_vec(),
+            "flags": flags,
+            "source": key(&source),
+            "offset": offset,
+        })
+    };
+    let account = json!({
+        "version": RecordData::CURRENT_VERSION,
+        "authority": key(&authority),
+        "slotCount": 2,
+        "flags": RecordData::CHECK_TOKEN_MINT | RecordData::PROGRAM_DERIVED,
+        "sourceOwner": key(&Pubkey::default()),
+        "tokenMint": key(&mint),
+        "tokenOwner": key(&Pubkey::default()),
+        "bump": 254,
+        "slots": [
+            slot("i16", Value::Signed(-2), RecordSlot::PINNED, 64),
+            slot("u64", Value::Unsigned(1_000_000), 0, RecordSlot::LAMPORTS_OFFSET),
+        ],
+    });
+    let mut data = vec![];
+    idl.encode(&idl.find("accounts", "record")["data"], &account, &mut data);
+    assert_eq!(data, expected);
+    assert_eq!(data.len(), RecordData::account_len(2));
+
+    let seeds = idl.find("pdas", "record")["seeds"].as_array().unwrap();
+    assert_eq!(
+        seeds[0]["value"]["data"].as_str().unwrap().as_bytes(),
+        RecordData::SEED_PREFIX
+    );
+}
+
+#[test]
+fn events_match_pack() {
+    let idl = Idl::load();
+    let record = Pubkey::new_unique();
+    let authority = Pubkey::new_unique();
+    let other = Pubkey::new_unique();
+    let cases = [
+        (
+            json!({ "initialize": {
+                "record": key(&record),
+                "authority": key(&authority),
+                "slotCount": 3,
+            }}),
+            RecordEvent::Initialize {
+                record: record.to_bytes(),
+                authority: authority.to_bytes(),
+                slot_count: 3,
+            },
+        ),
+        (
+            json!({ "write": {
+                "record": key(&record),
+                "slot": 2,
+                "valueType": "i32",
+                "value": Value::Signed(-5).encode().to_vec(),
+                "source": key(&other),
+                "offset": 64,
+                "pinned": true,
+            }}),
+            RecordEvent::Write {
+                record: record.to_bytes(),
+                slot: 2,
+                value_type: ValueType::I32,
+                value: Value::Signed(-5),
+                source: other.to_bytes(),
+                offset: 64,
+                pinned: true,
+            },
+        ),
+        (
+            json!({ "setAuthority": {
+                "record": key(&record),
+                "oldAuthority": key(&authority),
+                "newAuthority": key(&other),
+            }}),
+            RecordEvent::SetAuthority {
+                record: record.to_bytes(),
+                old_authority: authority.to_bytes(),
+                new_authority: other.to_bytes(),
+            },
+        ),
+        (
+            json!({ "close": {
+                "record": key(&record),
+                "destination": key(&other),
+                "lamports": 1_000_000,
+            }}),
+            RecordEvent::Close {
+                record: record.to_bytes(),
+                destination: other.to_bytes(),
+                lamports: 1_000_000,
+            },
+        ),
+    ];
+    let event_type = &idl.find("definedTypes", "recordEvent")["type"];
+    for (kind, event) in cases {
+        let mut data = vec![];
+        idl.encode(
+            event_type,
+            &json!({ "version": record::event::EVENT_VERSION, "event": kind }),
+            &mut data,
+        );
+        assert_eq!(data, event.pack());
+    }
+}
+
+#[test]
+fn errors_match_codes() {
+    let idl = Idl::load();
+    let errors = idl.program()["errors"].as_array().unwrap();
+    for (code, error) in errors.iter().enumerate() {
+        assert_eq!(error["code"], code);
+        let variant = format!("{:?}", RecordError::from_u32(code as u32).unwrap());
+        let name = error["name"].as_str().unwrap();
+        // IDL names are camelCase, variants PascalCase
+        assert_eq!(name[..1].to_uppercase() + &name[1..], variant);
+    }
+    assert!(RecordError::from_u32(errors.len() as u32).is_none());
+}
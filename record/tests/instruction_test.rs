@@ -0,0 +1,298 @@
+use record::{
+    error::RecordError,
+    instruction::{Comparison, RecordInstruction},
+    pinocchio::program_error::ProgramError,
+    state::RecordData,
+    value::{BitField, Encoding, Value, ValueType},
+};
+
+#[test]
+fn pack_unpack_roundtrip() {
+    let instructions = [
+        RecordInstruction::Initialize {
+            source_owner: None,
+            token_mint: None,
+            token_owner: None,
+        },
+        RecordInstruction::Initialize {
+            source_owner: Some([7; 32]),
+            token_mint: Some([8; 32]),
+            token_owner: None,
+        },
+        RecordInstruction::WriteU64 {
+            slot: 3,
+            offset: 64,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+            pin_source: true,
+        },
+        RecordInstruction::CheckAdd {
+            slot: 1,
+            offset: 64,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+            addition: 42,
+        },
+        RecordInstruction::SetAuthority,
+        RecordInstruction::CloseAccount,
+        RecordInstruction::CheckSub {
+            slot: 2,
+            offset: 121,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+            min_decrease: 1,
+            max_decrease: 10,
+        },
+        RecordInstruction::CheckCompare {
+            slot: 0,
+            offset: 64,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+            op: Comparison::Le,
+            operand: 7,
+        },
+        RecordInstruction::CheckCompare {
+            slot: 0,
+            offset: 64,
+            value_type: ValueType::I128,
+            encoding: Encoding {
+                big_endian: true,
+                bit_field: Some(BitField {
+                    mask: u128::MAX >> 1,
+                    shift: 3,
+                }),
+            },
+            op: Comparison::Range { upper: 9 },
+            operand: 7,
+        },
+        RecordInstruction::CheckAddBps {
+            slot: 4,
+            offset: 64,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+            bps: 30,
+        },
+        RecordInstruction::CheckCompareAccounts {
+            offset_a: 64,
+            offset_b: 8,
+            value_type: ValueType::U64,
+            encoding: Encoding::default(),
+            op: Comparison::Ge,
+            operand: 0,
+            owner_a: Some([9; 32]),
+            owner_b: None,
+        },
+        RecordInstruction::WriteTokenAmount { slot: 1 },
+        RecordInstruction::CheckTokenAdd {
+            slot: 1,
+            addition: 5,
+        },
+        RecordInstruction::WriteMintSupply { slot: 2 },
+        RecordInstruction::CheckMintSupplyAdd {
+            slot: 2,
+            addition: 6,
+        },
+        RecordInstruction::WriteLamports { slot: 3 },
+        RecordInstruction::CheckLamportsAdd {
+            slot: 3,
+            addition: 7,
+        },
+        RecordInstruction::InitializePda {
+            slot_count: 4,
+            seed: b"vault".to_vec(),
+            bump: 254,
+            source_owner: None,
+            token_mint: Some([4; 32]),
+            token_owner: None,
+        },
+        RecordInstruction::ReadU64 { slot: 5 },
+    ];
+    for instruction in instructions {
+        let packed = instruction.pack();
+        assert_eq!(RecordInstruction::unpack(&packed).unwrap(), instruction);
+        // every field is required
+        assert!(RecordInstruction::unpack(&packed[..packed.len() - 1]).is_err());
+    }
+}
+
+#[test]
+fn comparison_evaluate() {
+    let u = Value::Unsigned;
+    assert!(Comparison::Eq.evaluate(u(10), u(10), 0).unwrap());
+    assert!(!Comparison::Ne.evaluate(u(10), u(10), 0).unwrap());
+    assert!(Comparison::Lt.evaluate(u(14), u(10), 5).unwrap());
+    assert!(Comparison::Le.evaluate(u(15), u(10), 5).unwrap());
+    assert!(Comparison::Gt.evaluate(u(16), u(10), 5).unwrap());
+    assert!(Comparison::Ge.evaluate(u(15), u(10), 5).unwrap());
+    assert!(!Comparison::Ge.evaluate(u(14), u(10), 5).unwrap());
+
+    let range = Comparison::Range { upper: 20 };
+    assert!(range.evaluate(u(15), u(10), 5).unwrap());
+    assert!(range.evaluate(u(30), u(10), 5).unwrap());
+    assert!(!range.evaluate(u(14), u(10), 5).unwrap());
+    assert!(!range.evaluate(u(31), u(10), 5).unwrap());
+
+    let i = Value::Signed;
+    assert!(Comparison::Ge.evaluate(i(-5), i(-10), 5).unwrap());
+    assert!(!Comparison::Gt.evaluate(i(-5), i(-10), 5).unwrap());
+
+    assert_eq!(
+        Comparison::Ge.evaluate(u(u128::MAX), u(u128::MAX), 1),
+        Err(RecordError::Overflow)
+    );
+}
+
+#[test]
+fn value_type_read() {
+    let le = Encoding::default();
+    let data = [0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f];
+    assert_eq!(ValueType::U8.read(&data, 0, &le), Some(Value::Unsigned(1)));
+    assert_eq!(
+        ValueType::U16.read(&data, 0, &le),
+        Some(Value::Unsigned(0xff01))
+    );
+    assert_eq!(ValueType::I8.read(&data, 1, &le), Some(Value::Signed(-1)));
+    assert_eq!(ValueType::I64.read(&data, 1, &le), Some(Value::Signed(-1)));
+    assert_eq!(
+        ValueType::U64.read(&data, 1, &le),
+        Some(Value::Unsigned(u64::MAX as u128))
+    );
+    assert_eq!(
+        ValueType::I16.read(&data, 8, &le),
+        Some(Value::Signed(i16::MAX as i128))
+    );
+    assert_eq!(ValueType::U128.read(&data, 0, &le), None);
+    assert_eq!(ValueType::U32.read(&data, 7, &le), None);
+    assert_eq!(ValueType::U8.read(&data, usize::MAX, &le), None);
+
+    let wide = u128::MAX - 1;
+    assert_eq!(
+        ValueType::U128.read(&wide.to_le_bytes(), 0, &le),
+        Some(Value::Unsigned(wide))
+    );
+    let negative = Value::Signed(-42);
+    assert_eq!(ValueType::I32.decode(negative.encode()), negative);
+}
+
+#[test]
+fn value_type_read_encoding() {
+    let data = [0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0];
+    let be = Encoding {
+        big_endian: true,
+        bit_field: None,
+    };
+    assert_eq!(
+        ValueType::U16.read(&data, 0, &be),
+        Some(Value::Unsigned(0x1234))
+    );
+    assert_eq!(
+        ValueType::U64.read(&data, 0, &be),
+        Some(Value::Unsigned(0x1234_5678_9abc_def0))
+    );
+    assert_eq!(
+        ValueType::I64.read(&data, 0, &be),
+        Some(Value::Signed(0x1234_5678_9abc_def0))
+    );
+    assert_eq!(
+        ValueType::I32.read(&data, 4, &be),
+        Some(Value::Signed(-0x6543_2110))
+    );
+
+    // packed u16 in bits 16..32 of a little-endian u64
+    let packed_u16 = Encoding {
+        big_endian: false,
+        bit_field: Some(BitField {
+            mask: 0xffff_0000,
+            shift: 16,
+        }),
+    };
+    assert_eq!(
+        ValueType::U64.read(&data, 0, &packed_u16),
+        Some(Value::Unsigned(0x7856))
+    );
+    let packed_be = Encoding {
+        big_endian: true,
+        ..packed_u16
+    };
+    assert_eq!(
+        ValueType::U64.read(&data, 0, &packed_be),
+        Some(Value::Unsigned(0x9abc))
+    );
+}
+
+#[test]
+fn value_decrease() {
+    assert_eq!(Value::Unsigned(10).decrease_to(&Value::Unsigned(4)), 6);
+    assert_eq!(Value::Unsigned(4).decrease_to(&Value::Unsigned(10)), 0);
+    assert_eq!(Value::Signed(-4).decrease_to(&Value::Signed(-10)), 6);
+    assert_eq!(Value::Signed(-10).decrease_to(&Value::Signed(-4)), 0);
+    assert_eq!(
+        Value::Signed(i64::MAX as i128).decrease_to(&Value::Signed(i64::MIN as i128)),
+        u64::MAX as u128
+    );
+}
+
+#[test]
+fn value_increase() {
+    assert_eq!(Value::Unsigned(10).increase_from(&Value::Unsigned(4)), 6);
+    assert_eq!(Value::Unsigned(4).increase_from(&Value::Unsigned(10)), 0);
+    assert_eq!(Value::Signed(5).increase_from(&Value::Signed(-10)), 15);
+    assert_eq!(Value::Signed(-10).increase_from(&Value::Signed(5)), 0);
+}
+
+#[test]
+fn value_add_bps() {
+    assert_eq!(
+        Value::Unsigned(1_000).checked_add_bps(30),
+        Some(Value::Unsigned(1_003))
+    );
+    assert_eq!(
+        Value::Unsigned(u64::MAX as u128).checked_add_bps(u16::MAX),
+        Some(Value::Unsigned(u64::MAX as u128 * 75_535 / 10_000))
+    );
+    // growth of a negative value is towards zero
+    assert_eq!(
+        Value::Signed(-1_000).checked_add_bps(30),
+        Some(Value::Signed(-997))
+    );
+    assert_eq!(
+        Value::Signed(-1_001).checked_add_bps(30),
+        Some(Value::Signed(-998))
+    );
+    assert_eq!(
+        Value::Signed(i64::MIN as i128).checked_add_bps(10_000),
+        Some(Value::Signed(0))
+    );
+    assert_eq!(Value::Signed(i128::MIN).checked_add_bps(2), None);
+
+    // the required value must fit the recorded type, like for CheckAdd
+    assert_eq!(
+        ValueType::U8.checked_add_bps(Value::Unsigned(200), 2_750),
+        Some(Value::Unsigned(255))
+    );
+    assert_eq!(
+        ValueType::U8.checked_add_bps(Value::Unsigned(200), 10_000),
+        None
+    );
+    assert_eq!(
+        ValueType::I8.checked_add_bps(Value::Signed(-128), 10_000),
+        Some(Value::Signed(0))
+    );
+    assert_eq!(Value::Unsigned(u128::MAX).checked_add_bps(1), None);
+}
+
+#[test]
+fn unpack_seed_too_long() {
+    let instruction = RecordInstruction::InitializePda {
+        slot_count: 1,
+        seed: vec![7; RecordData::MAX_SEED_LEN + 1],
+        bump: 255,
+        source_owner: None,
+        token_mint: None,
+        token_owner: None,
+    };
+    assert_eq!(
+        RecordInstruction::unpack(&instruction.pack()),
+        Err(ProgramError::MaxSeedLengthExceeded)
+    );
+}
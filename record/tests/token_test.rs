@@ -0,0 +1,171 @@
+use record::{
+    error::RecordError,
+    token::{
+        TokenAccount, TokenMint, AMOUNT_OFFSET, SUPPLY_OFFSET, TOKEN_2022_PROGRAM_ID,
+        TOKEN_PROGRAM_ID,
+    },
+};
+use solana_program::{program_pack::Pack, pubkey::Pubkey};
+use solana_program_option::COption;
+use spl_token::state::{Account, AccountState, Mint};
+
+fn pack_token_account(state: AccountState) -> (Account, Vec<u8>) {
+    let account = Account {
+        mint: Pubkey::new_unique(),
+        owner: Pubkey::new_unique(),
+        amount: 999,
+        delegate: COption::Some(Pubkey::new_unique()),
+        state,
+        is_native: COption::None,
+        delegated_amount: 5,
+        close_authority: COption::None,
+    };
+    let mut data = vec![0u8; Account::LEN];
+    Account::pack(account, &mut data).unwrap();
+    (account, data)
+}
+
+fn pack_mint(is_initialized: bool) -> (Mint, Vec<u8>) {
+    let mint = Mint {
+        mint_authority: COption::Some(Pubkey::new_unique()),
+        supply: 1_000_000,
+        decimals: 6,
+        is_initialized,
+        freeze_authority: COption::None,
+    };
+    let mut data = vec![0u8; Mint::LEN];
+    Mint::pack_into_slice(&mint, &mut data);
+    (mint, data)
+}
+
+/// Appends the Token-2022 account type and a transfer fee extension entry
+fn extend(mut data: Vec<u8>, account_type: u8, extension_type: u16) -> Vec<u8> {
+    data.resize(Account::LEN, 0);
+    data.push(account_type);
+    data.extend_from_slice(&extension_type.to_le_bytes());
+    data.extend_from_slice(&8u16.to_le_bytes());
+    data.extend_from_slice(&7u64.to_le_bytes());
+    data
+}
+
+#[test]
+fn token_program_id() {
+    assert_eq!(TOKEN_PROGRAM_ID, spl_token::id().to_bytes());
+    assert_eq!(
+        TOKEN_2022_PROGRAM_ID,
+        Pubkey::from_str_const("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb").to_bytes()
+    );
+}
+
+#[test]
+fn unpack_token_account() {
+    let (account, data) = pack_token_account(AccountState::Initialized);
+    let token_account = TokenAccount::unpack(&TOKEN_PROGRAM_ID, &data).unwrap();
+    assert_eq!(token_account.mint, account.mint.to_bytes());
+    assert_eq!(token_account.owner, account.owner.to_bytes());
+    assert_eq!(token_account.amount, account.amount);
+    assert_eq!(
+        u64::from_le_bytes(data[AMOUNT_OFFSET..AMOUNT_OFFSET + 8].try_into().unwrap()),
+        account.amount
+    );
+
+    assert_eq!(
+        TokenAccount::unpack(&Pubkey::new_unique().to_bytes(), &data),
+        Err(RecordError::InvalidTokenAccount)
+    );
+    assert_eq!(
+        TokenAccount::unpack(&TOKEN_PROGRAM_ID, &data[..data.len() - 1]),
+        Err(RecordError::InvalidTokenAccount)
+    );
+
+    for state in [AccountState::Uninitialized, AccountState::Frozen] {
+        let (_, data) = pack_token_account(state);
+        assert_eq!(
+            TokenAccount::unpack(&TOKEN_PROGRAM_ID, &data),
+            Err(RecordError::TokenAccountNotInitialized)
+        );
+    }
+}
+
+#[test]
+fn unpack_token_2022_account() {
+    let (account, data) = pack_token_account(AccountState::Initialized);
+    let token_account = TokenAccount::unpack(&TOKEN_2022_PROGRAM_ID, &data).unwrap();
+    assert_eq!(token_account.amount, account.amount);
+
+    // transfer fee amount extension
+    let extended = extend(data.clone(), 2, 2);
+    let token_account = TokenAccount::unpack(&TOKEN_2022_PROGRAM_ID, &extended).unwrap();
+    assert_eq!(token_account.mint, account.mint.to_bytes());
+    assert_eq!(token_account.owner, account.owner.to_bytes());
+    assert_eq!(token_account.amount, account.amount);
+
+    // extensions are only known to Token-2022
+    assert_eq!(
+        TokenAccount::unpack(&TOKEN_PROGRAM_ID, &extended),
+        Err(RecordError::InvalidTokenAccount)
+    );
+    // a mint padded to the account layout
+    assert_eq!(
+        TokenAccount::unpack(&TOKEN_2022_PROGRAM_ID, &extend(data.clone(), 1, 1)),
+        Err(RecordError::InvalidTokenAccount)
+    );
+    // multisig accounts share no layout with extended accounts
+    let mut multisig = extended.clone();
+    multisig.resize(355, 0);
+    assert_eq!(
+        TokenAccount::unpack(&TOKEN_2022_PROGRAM_ID, &multisig),
+        Err(RecordError::InvalidTokenAccount)
+    );
+}
+
+#[test]
+fn unpack_token_mint() {
+    let (mint, data) = pack_mint(true);
+    for owner_program in [TOKEN_PROGRAM_ID, TOKEN_2022_PROGRAM_ID] {
+        let token_mint = TokenMint::unpack(&owner_program, &data).unwrap();
+        assert_eq!(token_mint.supply, mint.supply);
+    }
+    assert_eq!(
+        u64::from_le_bytes(data[SUPPLY_OFFSET..SUPPLY_OFFSET + 8].try_into().unwrap()),
+        mint.supply
+    );
+
+    // transfer fee config extension
+    let extended = extend(data.clone(), 1, 1);
+    assert_eq!(
+        TokenMint::unpack(&TOKEN_2022_PROGRAM_ID, &extended)
+            .unwrap()
+            .supply,
+        mint.supply
+    );
+    assert_eq!(
+        TokenMint::unpack(&TOKEN_PROGRAM_ID, &extended),
+        Err(RecordError::InvalidMint)
+    );
+    assert_eq!(
+        TokenMint::unpack(&TOKEN_2022_PROGRAM_ID, &extend(data.clone(), 2, 2)),
+        Err(RecordError::InvalidMint)
+    );
+    assert_eq!(
+        TokenMint::unpack(&Pubkey::new_unique().to_bytes(), &data),
+        Err(RecordError::InvalidMint)
+    );
+    assert_eq!(
+        TokenMint::unpack(&TOKEN_PROGRAM_ID, &data[..data.len() - 1]),
+        Err(RecordError::InvalidMint)
+    );
+
+    let (_, data) = pack_mint(false);
+    assert_eq!(
+        TokenMint::unpack(&TOKEN_PROGRAM_ID, &data),
+        Err(RecordError::InvalidMint)
+    );
+
+    // an account layout is not a mint
+    let (_, data) = pack_token_account(AccountState::Initialized);
+    assert_eq!(
+        TokenMint::unpack(&TOKEN_PROGRAM_ID, &data),
+        Err(RecordError::InvalidMint)
+    );
+}